@@ -0,0 +1,820 @@
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+use macroquad_stuff::{
+    Context, Emitter, GameInfo, GameState, KeyRepeat, OpenOptions, ParticleSystem, PauseItem,
+    SETTING_DEBUG_OVERLAY, ScaleMode, SoundEffect, TextLayout, Viewport, Waveform,
+};
+
+/// Width and height of the square world the game draws in
+const WORLD_SIZE: f32 = 1000.0;
+/// Space around the grid for the help and the delay slider
+const BORDER: f32 = 100.0;
+
+#[derive(Clone, Copy)]
+enum GridMode {
+    Lines,
+    Shaded,
+    None,
+}
+
+impl GridMode {
+    fn name(self) -> &'static str {
+        match self {
+            GridMode::Lines => "lines",
+            GridMode::Shaded => "shaded",
+            GridMode::None => "none",
+        }
+    }
+
+    fn from_index(index: u8) -> Self {
+        match index {
+            1 => GridMode::Shaded,
+            2 => GridMode::None,
+            _ => GridMode::Lines,
+        }
+    }
+}
+
+/// Neighbor counts for which a dead cell becomes alive and a live cell stays alive
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    birth: [bool; 9],
+    survive: [bool; 9],
+}
+
+impl Rule {
+    /// Conway's rule: a cell becomes active with three neighbors
+    /// and keeps its state with two or three
+    const CONWAY: Self = Self {
+        birth: [false, false, false, true, false, false, false, false, false],
+        survive: [false, false, true, true, false, false, false, false, false],
+    };
+
+    /// Parses the B/S notation, e.g. "B36/S23"
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.to_uppercase();
+        let (birth, survive) = text.split_once('/')?;
+        let birth = birth.strip_prefix('B')?;
+        let survive = survive.strip_prefix('S')?;
+        let counts = |digits: &str| -> Option<[bool; 9]> {
+            let mut counts = [false; 9];
+            for digit in digits.chars() {
+                let n = digit.to_digit(10).filter(|n| *n <= 8)?;
+                counts[n as usize] = true;
+            }
+            Some(counts)
+        };
+        Some(Self {
+            birth: counts(birth)?,
+            survive: counts(survive)?,
+        })
+    }
+
+    fn name(self) -> String {
+        let digits = |counts: [bool; 9]| -> String {
+            (0..9)
+                .filter(|n| counts[*n])
+                .map(|n| n.to_string())
+                .collect()
+        };
+        format!("B{}/S{}", digits(self.birth), digits(self.survive))
+    }
+}
+
+const START_SIZE: usize = 40;
+/// Most rows and columns the console can resize the grid to
+const MAX_SIZE: usize = 500;
+/// Most generations a single console step runs, more would stall the frame
+const MAX_STEPS: u32 = 1000;
+
+const TOGGLE_DRAWING: &str = "toggle_drawing";
+const RESET: &str = "reset";
+const SLOWER: &str = "slower";
+const FASTER: &str = "faster";
+const SHRINK: &str = "shrink";
+const GROW: &str = "grow";
+const TOGGLE_GRID: &str = "toggle_grid";
+const TOGGLE_PAUSE: &str = "toggle_pause";
+const STEP: &str = "step";
+const OPEN: &str = "open";
+const EXPORT: &str = "export";
+
+const SETTING_START_SIZE: &str = "start_size";
+const SETTING_CELL_COLOR: &str = "cell_color";
+const SETTING_TICK_VOLUME: &str = "tick_volume";
+const SOUND_TICK: &str = "tick";
+const CHANNEL_TICKS: &str = "ticks";
+const CELL_COLORS: [Color; 3] = [GREEN, SKYBLUE, ORANGE];
+
+pub struct State {
+    rows: usize,
+    cols: usize,
+    cells: Vec<bool>,
+    next_cells: Vec<bool>,
+    reset_cells: Vec<bool>,
+    step_time: f32,
+    /// Step time in the storage, saved once it stops changing
+    saved_step_time: f32,
+    last_step_time: f32,
+    time_elapsed: f32,
+    drawing_mode: bool,
+    grid_mode: GridMode,
+    paused: bool,
+    cell_color: Color,
+    /// Steps since the last reset or drawing
+    generation: u64,
+    rule: Rule,
+    /// Flashes of the cells born and died in the last steps, in world units
+    particles: ParticleSystem,
+    /// Keys of the actions for the help, looked up in the update as the player can rebind them
+    action_keys: HashMap<&'static str, String>,
+}
+impl Default for State {
+    fn default() -> Self {
+        let mut state = Self {
+            rows: START_SIZE,
+            cols: START_SIZE,
+            cells: vec![false; START_SIZE * START_SIZE],
+            next_cells: vec![false; START_SIZE * START_SIZE],
+            reset_cells: vec![],
+            step_time: 0.5,
+            saved_step_time: 0.5,
+            last_step_time: 0.5,
+            time_elapsed: 0.0,
+            drawing_mode: false,
+            grid_mode: GridMode::Lines,
+            paused: false,
+            cell_color: GREEN,
+            generation: 0,
+            rule: Rule::CONWAY,
+            particles: ParticleSystem::default(),
+            action_keys: HashMap::new(),
+        };
+        state.spawn_glider();
+        state.reset_cells = state.cells.clone();
+        state
+    }
+}
+
+impl GameInfo for State {
+    fn name() -> &'static str {
+        "Convay"
+    }
+
+    fn description() -> &'static str {
+        "Conway's Game of Life"
+    }
+}
+
+impl GameState for State {
+    fn bg_color(&self) -> Color {
+        BLACK
+    }
+    fn viewport() -> Option<Viewport> {
+        Some(Viewport::new(WORLD_SIZE, WORLD_SIZE, ScaleMode::Fit))
+    }
+    fn init(ctx: &mut Context<Self>) {
+        ctx.define_action(TOGGLE_DRAWING, &[KeyCode::Space]);
+        ctx.define_action(RESET, &[KeyCode::R]);
+        ctx.define_action(SLOWER, &[KeyCode::Up]);
+        ctx.define_action(FASTER, &[KeyCode::Down]);
+        ctx.define_action(SHRINK, &[KeyCode::Left]);
+        ctx.define_action(GROW, &[KeyCode::Right]);
+        ctx.define_action(TOGGLE_GRID, &[KeyCode::G]);
+        ctx.define_action(TOGGLE_PAUSE, &[KeyCode::P]);
+        ctx.define_action(STEP, &[KeyCode::S]);
+        ctx.define_action(OPEN, &[KeyCode::O]);
+        ctx.define_action(EXPORT, &[KeyCode::E]);
+        ctx.set_action_repeat(SHRINK, KeyRepeat::new(0.4, 0.08));
+        ctx.set_action_repeat(GROW, KeyRepeat::new(0.4, 0.08));
+        ctx.set_action_repeat(STEP, KeyRepeat::new(0.4, 0.1));
+        let buttons = [
+            (TOGGLE_DRAWING, "Draw"),
+            (TOGGLE_PAUSE, "Pause"),
+            (STEP, "Step"),
+            (TOGGLE_GRID, "Grid"),
+            (SLOWER, "Slower"),
+            (FASTER, "Faster"),
+            (SHRINK, "Shrink"),
+            (GROW, "Grow"),
+            (RESET, "Reset"),
+            (OPEN, "Open"),
+        ];
+        for (i, (action, label)) in buttons.into_iter().enumerate() {
+            let x = i as f32 * 0.1;
+            ctx.add_virtual_button(action, label, Rect::new(x + 0.005, 0.91, 0.09, 0.08));
+        }
+        if let Some(step_time) = ctx.storage().get("step_time") {
+            ctx.state.step_time = step_time;
+            ctx.state.saved_step_time = step_time;
+        }
+        if let Some(grid_mode) = ctx.storage().get("grid_mode") {
+            ctx.state.grid_mode = GridMode::from_index(grid_mode);
+        }
+        ctx.define_range_setting(
+            SETTING_START_SIZE,
+            "Start size",
+            10.0,
+            100.0,
+            5.0,
+            START_SIZE as f32,
+        );
+        ctx.define_choice_setting(
+            SETTING_CELL_COLOR,
+            "Cell color",
+            &["green", "blue", "orange"],
+            0,
+        );
+        let size = ctx.setting_value(SETTING_START_SIZE) as usize;
+        ctx.state.resize(size, size);
+        ctx.define_range_setting(SETTING_TICK_VOLUME, "Step sound", 0.0, 1.0, 0.1, 0.5);
+        let tick = SoundEffect::new(Waveform::Triangle, 880.0)
+            .slide_to(660.0)
+            .duration(0.03)
+            .volume(0.3);
+        ctx.add_sound(SOUND_TICK, CHANNEL_TICKS, tick);
+        ctx.add_command("resize", "<rows> <cols>", |ctx, args| {
+            let rows = args.get(0)?;
+            let cols = args.get(1)?;
+            if rows == 0 || cols == 0 {
+                return Err("the grid needs at least one row and column".to_string());
+            }
+            if rows > MAX_SIZE || cols > MAX_SIZE {
+                return Err(format!(
+                    "the grid can have at most {MAX_SIZE} rows and columns"
+                ));
+            }
+            ctx.state.resize(rows, cols);
+            Ok(())
+        });
+        ctx.add_command("rule", "[B3/S23]", |ctx, args| {
+            if let Some(text) = args.text(0) {
+                ctx.state.rule = Rule::parse(text)
+                    .ok_or_else(|| format!("'{text}' is not a rule like B3/S23"))?;
+            }
+            let rule = ctx.state.rule.name();
+            ctx.console_print(&format!("rule {rule}"));
+            Ok(())
+        });
+        ctx.set_command_completion("rule", |_, i| {
+            // Conway's, HighLife, Seeds and Day & Night
+            let rules = ["B3/S23", "B36/S23", "B2/S", "B3678/S34678"];
+            if i == 0 {
+                rules.map(ToString::to_string).to_vec()
+            } else {
+                Vec::new()
+            }
+        });
+        ctx.add_command("step", "[count]", |ctx, args| {
+            let count: u32 = args.get_or(0, 1)?;
+            if count > MAX_STEPS {
+                return Err(format!("at most {MAX_STEPS} steps at once"));
+            }
+            for _ in 0..count {
+                ctx.state.update_cells();
+            }
+            Ok(())
+        });
+    }
+    async fn update(ctx: &mut Context<Self>, delta_time: f32) {
+        handle_input(ctx).await;
+        ctx.state.particles.update(delta_time);
+        ctx.state.time_elapsed += delta_time;
+        if ctx.state.drawing_mode
+            || ctx.state.time_elapsed < ctx.state.step_time
+            || ctx.state.paused
+        {
+            return;
+        }
+        ctx.state.last_step_time = ctx.state.time_elapsed;
+        ctx.state.time_elapsed = 0.0;
+        ctx.state.update_cells();
+        ctx.play_sound(SOUND_TICK);
+    }
+    fn draw(&self, _alpha: f32) {
+        let grid = grid_rect();
+
+        let mut help = TextLayout::new("").size(30.0).line_spacing(1.0);
+        let keys = [
+            (self.keys(TOGGLE_DRAWING).to_string(), "draw"),
+            (self.keys(RESET).to_string(), "reset"),
+            (
+                format!("{}/{}", self.keys(SLOWER), self.keys(FASTER)),
+                "delay",
+            ),
+            (format!("{}/{}", self.keys(SHRINK), self.keys(GROW)), "size"),
+            (self.keys(TOGGLE_GRID).to_string(), "grid mode"),
+        ];
+        let count = keys.len();
+        for (i, (key, action)) in keys.into_iter().enumerate() {
+            let separator = if i + 1 < count { ", " } else { "" };
+            help = help
+                .span(&key, YELLOW)
+                .span(&format!(": {action}{separator}"), WHITE);
+        }
+        let status = if self.drawing_mode {
+            format!(
+                "drawing mode. press {} to continue, {} to open, {} to export",
+                self.keys(TOGGLE_DRAWING),
+                self.keys(OPEN),
+                self.keys(EXPORT)
+            )
+        } else if self.paused {
+            format!(
+                "Paused, {} to continue, {} to step",
+                self.keys(TOGGLE_PAUSE),
+                self.keys(STEP)
+            )
+        } else {
+            format!(
+                "Delay Target: {:.1}s, Delay: {:.2}s; {} to Pause",
+                self.step_time,
+                self.last_step_time,
+                self.keys(TOGGLE_PAUSE)
+            )
+        };
+        help.span(&format!("\n{status}"), WHITE).draw_in(Rect::new(
+            5.0,
+            5.0,
+            WORLD_SIZE - 10.0,
+            BORDER - 10.0,
+        ));
+
+        let line_thickness = if matches!(self.grid_mode, GridMode::Lines) {
+            2.0
+        } else {
+            0.0
+        };
+        let cw = grid.w / self.cols as f32;
+        let ch = grid.h / self.rows as f32;
+        let offset = line_thickness / 2.0;
+
+        for row in 0..self.rows {
+            let y = row as f32 * ch + grid.y;
+            if matches!(self.grid_mode, GridMode::Lines) && row > 0 {
+                draw_line(grid.x, y, grid.right(), y, line_thickness, WHITE);
+            }
+            for col in 0..self.cols {
+                let x = col as f32 * cw + grid.x;
+                if matches!(self.grid_mode, GridMode::Lines) && col > 0 && row == 0 {
+                    draw_line(x, grid.y, x, grid.bottom(), line_thickness, WHITE);
+                }
+                let cell_color = if self.cells[self.get_index(col, row)] {
+                    self.cell_color
+                } else if matches!(self.grid_mode, GridMode::Shaded) {
+                    if row % 2 == col % 2 { GRAY } else { DARKGRAY }
+                } else {
+                    WHITE
+                };
+                if cell_color != WHITE {
+                    draw_rectangle(
+                        x + offset,
+                        y + offset,
+                        cw - offset * 2.0,
+                        ch - offset * 2.0,
+                        cell_color,
+                    );
+                }
+            }
+        }
+        self.particles.draw();
+        draw_rectangle_lines(grid.x, grid.y, grid.w, grid.h, 4.0, WHITE);
+    }
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    async fn update_paused(ctx: &mut Context<Self>, delta_time: f32) {
+        handle_input(ctx).await;
+        ctx.state.particles.update(delta_time);
+    }
+
+    fn pause_items(&self) -> Vec<PauseItem<Self>> {
+        let drawing = if self.drawing_mode {
+            "Stop drawing"
+        } else {
+            "Draw a shape"
+        };
+        vec![
+            PauseItem::new(format!("Grid: {}", self.grid_mode.name()), cycle_grid_mode),
+            PauseItem::new(drawing, |ctx| {
+                toggle_drawing(ctx);
+                ctx.clear_scenes();
+            }),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.cells.clone_from(&self.reset_cells);
+        self.time_elapsed = 0.0;
+        self.generation = 0;
+        self.particles.clear();
+    }
+}
+
+impl State {
+    fn update_cells(&mut self) {
+        let grid = grid_rect();
+        let cell = vec2(grid.w / self.cols as f32, grid.h / self.rows as f32);
+        let transparent = Color::new(1.0, 1.0, 1.0, 0.0);
+        let birth = Emitter::new()
+            .speed(0.0, 0.0)
+            .lifetime(0.3, 0.3)
+            .color(self.cell_color, transparent)
+            .size(cell.x * 0.5, cell.x * 1.5);
+        let death = Emitter::new()
+            .speed(0.0, 0.0)
+            .lifetime(0.4, 0.4)
+            .color(GRAY, transparent)
+            .size(cell.x, 0.0);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let mut neighbors = 0;
+                for n_row in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
+                    for n_col in col.saturating_sub(1)..=(col + 1).min(self.cols - 1) {
+                        // skip self
+                        if n_col == col && n_row == row {
+                            continue;
+                        }
+                        // check neighbor
+                        if self.cells[n_row * self.cols + n_col] {
+                            neighbors += 1;
+                        }
+                    }
+                }
+                // apply rules
+                let alive = self.cells[row * self.cols + col];
+                let next = if alive {
+                    self.rule.survive[neighbors]
+                } else {
+                    self.rule.birth[neighbors]
+                };
+                self.next_cells[row * self.cols + col] = next;
+                if alive != next {
+                    let center = grid.point() + (vec2(col as f32, row as f32) + 0.5) * cell;
+                    let flash = if next { &birth } else { &death };
+                    self.particles.burst(flash, center, 1);
+                }
+            }
+        }
+        // swap cells
+        std::mem::swap(&mut self.cells, &mut self.next_cells);
+        self.generation += 1;
+    }
+
+    fn spawn_glider(&mut self) {
+        // spawn glider in top left corner
+        for (x, y) in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)] {
+            let idx = y * self.cols + x;
+            if idx >= self.cells.len() {
+                continue;
+            }
+            self.cells[idx] = true;
+        }
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        if rows < 1 || cols < 1 {
+            return;
+        }
+        std::mem::swap(&mut self.cells, &mut self.next_cells);
+        self.cells.resize(rows * cols, false);
+        self.cells.fill(false);
+        // map cells to new indices
+        for row in 0..rows {
+            for col in 0..cols {
+                if col >= self.cols || row >= self.rows {
+                    continue;
+                }
+                let old_index = row * self.cols + col;
+                let new_index = row * cols + col;
+                if new_index < self.cells.len() {
+                    self.cells[new_index] = self.next_cells[old_index];
+                }
+            }
+        }
+        std::mem::swap(&mut self.reset_cells, &mut self.next_cells);
+        self.reset_cells.resize(rows * cols, false);
+        self.reset_cells.fill(false);
+        // map cells to new indices
+        for row in 0..rows {
+            for col in 0..cols {
+                if col >= self.cols || row >= self.rows {
+                    continue;
+                }
+                let old_index = row * self.cols + col;
+                let new_index = row * cols + col;
+                if new_index < self.cells.len() {
+                    self.reset_cells[new_index] = self.next_cells[old_index];
+                }
+            }
+        }
+        self.next_cells.resize(rows * cols, false);
+        self.rows = rows;
+        self.cols = cols;
+    }
+
+    /// Keys of the action as shown in the help, e.g. "Space"
+    fn keys(&self, action: &str) -> &str {
+        self.action_keys.get(action).map_or("", String::as_str)
+    }
+
+    /// Index of the cell in column `x` and row `y`, rows are stored one after another
+    fn get_index(&self, x: usize, y: usize) -> usize {
+        y * self.cols + x
+    }
+}
+
+fn toggle_drawing(ctx: &mut Context<State>) {
+    if ctx.state.drawing_mode {
+        // save drawing for reset
+        ctx.state.reset_cells.clone_from(&ctx.state.cells);
+        info!("Saved drawing");
+    } else {
+        ctx.state.cells.fill(false);
+        ctx.state.generation = 0;
+    }
+    ctx.state.drawing_mode = !ctx.state.drawing_mode;
+}
+
+fn cycle_grid_mode(ctx: &mut Context<State>) {
+    ctx.state.grid_mode = match ctx.state.grid_mode {
+        GridMode::Lines => GridMode::Shaded,
+        GridMode::Shaded => GridMode::None,
+        GridMode::None => GridMode::Lines,
+    };
+    let grid_mode = ctx.state.grid_mode as u8;
+    ctx.storage().set("grid_mode", &grid_mode);
+}
+
+async fn handle_input(ctx: &mut Context<State>) {
+    let actions = [
+        TOGGLE_DRAWING,
+        RESET,
+        SLOWER,
+        FASTER,
+        SHRINK,
+        GROW,
+        TOGGLE_GRID,
+        TOGGLE_PAUSE,
+        STEP,
+        OPEN,
+        EXPORT,
+    ];
+    for action in actions {
+        let keys = ctx.action_keys_text(action);
+        ctx.state.action_keys.insert(action, keys);
+    }
+    // defined in init or changed in the settings menu
+    if ctx.take_setting_change(SETTING_CELL_COLOR) {
+        ctx.state.cell_color = CELL_COLORS[ctx.setting_choice(SETTING_CELL_COLOR)];
+    }
+    if ctx.take_setting_change(SETTING_TICK_VOLUME) {
+        let tick_volume = ctx.setting_value(SETTING_TICK_VOLUME);
+        ctx.set_channel_volume(CHANNEL_TICKS, tick_volume);
+    }
+    if ctx.is_action_pressed(TOGGLE_DRAWING) {
+        toggle_drawing(ctx);
+    }
+    if ctx.is_action_pressed(RESET) {
+        ctx.state.reset();
+    }
+    let grid = grid_rect();
+    // below the grid, above the virtual buttons. widgets are drawn on the screen
+    let top_left = ctx.world_to_screen(vec2(grid.x, grid.bottom() + 10.0));
+    let bottom_right = ctx.world_to_screen(vec2(grid.right(), grid.bottom() + 40.0));
+    let size = bottom_right - top_left;
+    let slider = Rect::new(top_left.x, top_left.y, size.x, size.y);
+    let label = format!("Delay: {:.2}s", ctx.state.step_time);
+    let mut step_time = ctx.state.step_time;
+    let mut changed = ctx.ui_slider(slider, &label, &mut step_time, 0.0, 2.0);
+    if ctx.is_action_pressed(SLOWER) {
+        step_time += 0.1;
+        changed = true;
+    }
+    if ctx.is_action_pressed(FASTER) {
+        step_time -= 0.1;
+        changed = true;
+    }
+    if changed {
+        let step_time = ((step_time * 20.0).round() / 20.0).clamp(0.0, 2.0);
+        ctx.state.step_time = step_time;
+    } else if ctx.state.step_time != ctx.state.saved_step_time {
+        // not while dragging, every save writes the whole file
+        let step_time = ctx.state.step_time;
+        ctx.storage().set("step_time", &step_time);
+        ctx.state.saved_step_time = step_time;
+    }
+    // Left/Right move the slider while it has the keyboard, Tab or a click elsewhere ends that
+    let slider_focused = ctx.ui_focus().is_some();
+    if !slider_focused && ctx.is_action_pressed_loop(SHRINK) {
+        ctx.state.resize(ctx.state.rows - 1, ctx.state.cols - 1);
+    }
+    if !slider_focused && ctx.is_action_pressed_loop(GROW) {
+        ctx.state.resize(ctx.state.rows + 1, ctx.state.cols + 1);
+    }
+    if ctx.is_action_pressed(TOGGLE_GRID) {
+        cycle_grid_mode(ctx);
+    }
+    if !ctx.state.drawing_mode && ctx.is_action_pressed(TOGGLE_PAUSE) {
+        ctx.state.paused = !ctx.state.paused;
+    }
+    if ctx.state.paused && ctx.is_action_pressed_loop(STEP) {
+        // do a single step
+        ctx.state.update_cells();
+        ctx.play_sound(SOUND_TICK);
+    }
+    if ctx.state.drawing_mode {
+        if ctx.is_action_pressed(OPEN) {
+            load_from_file(ctx).await;
+        }
+        if ctx.is_action_pressed(EXPORT) {
+            save_to_file(ctx).await;
+        }
+        if ctx.is_mouse_button_pressed(MouseButton::Left)
+            && !ctx.is_on_virtual_button(ctx.mouse_position())
+        {
+            let mouse_pos = ctx.world_mouse_position();
+            if grid.contains(mouse_pos) {
+                let cw = grid.w / ctx.state.cols as f32;
+                let ch = grid.h / ctx.state.rows as f32;
+                let x = ((mouse_pos.x - grid.x) / cw) as usize;
+                let y = ((mouse_pos.y - grid.y) / ch) as usize;
+                if x < ctx.state.cols && y < ctx.state.rows {
+                    let index = ctx.state.get_index(x, y);
+                    ctx.state.cells[index] = !ctx.state.cells[index];
+                }
+            }
+        }
+    }
+    if ctx.setting_bool(SETTING_DEBUG_OVERLAY) {
+        let generation = ctx.state.generation;
+        let population = ctx.state.cells.iter().filter(|alive| **alive).count();
+        ctx.debug_watch("generation", generation);
+        ctx.debug_watch("population", population);
+    }
+}
+
+async fn load_from_file(ctx: &mut Context<State>) {
+    let options = OpenOptions::new()
+        .title("Open shape")
+        .filter("Shapes", &["txt"]);
+    let file = match ctx.open_file_with(&options).await {
+        Ok(Some(file)) => file,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to open file: {e}");
+            return;
+        }
+    };
+    match file.extension().as_deref() {
+        Some("txt") | None => {}
+        Some(ext) => {
+            warn!("Unsupported file type: {ext}");
+            return;
+        }
+    }
+    let text = file.text();
+    for line in text.lines() {
+        if line.starts_with("//") || line.is_empty() {
+            continue;
+        }
+        let (Ok(x), Ok(y)) = ({
+            let (x, y) = line.split_once(' ').unwrap();
+            let x = x.parse();
+            let y = y.parse();
+            (x, y)
+        }) else {
+            println!("Invalid line: {line}");
+            continue;
+        };
+        if x < ctx.state.cols && y < ctx.state.rows {
+            let index = ctx.state.get_index(x, y);
+            ctx.state.cells[index] = true;
+        }
+    }
+}
+
+async fn save_to_file(ctx: &mut Context<State>) {
+    let mut text = String::from("// convay shape\n");
+    for x in 0..ctx.state.cols {
+        for y in 0..ctx.state.rows {
+            if ctx.state.cells[ctx.state.get_index(x, y)] {
+                text.push_str(&format!("{x} {y}\n"));
+            }
+        }
+    }
+    match ctx.save_file("shape.txt", text.as_bytes()).await {
+        Ok(true) => info!("Saved shape"),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to save shape: {e}"),
+    }
+}
+
+/// Area of the grid in world units
+fn grid_rect() -> Rect {
+    let size = WORLD_SIZE - BORDER * 2.0;
+    Rect::new(BORDER, BORDER, size, size)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use macroquad_stuff::Harness;
+
+    use super::*;
+
+    fn live_cells(state: &State) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for y in 0..state.rows {
+            for x in 0..state.cols {
+                if state.cells[state.get_index(x, y)] {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn rule_parses_the_bs_notation() {
+        assert!(Rule::parse("B3/S23") == Some(Rule::CONWAY));
+        let highlife = Rule::parse("b36/s23").unwrap();
+        assert_eq!(highlife.name(), "B36/S23");
+        assert_eq!(Rule::parse("B2/S").unwrap().name(), "B2/S");
+        for invalid in ["", "B3", "S23/B3", "B9/S23", "B3/S2x"] {
+            assert!(Rule::parse(invalid).is_none(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn glider_moves_diagonally_every_4_generations() {
+        let mut harness = Harness::<State>::new();
+        let start = live_cells(harness.state());
+        assert_eq!(start.len(), 5);
+
+        assert!(harness.run_until(600, |state| state.generation == 4));
+        let moved: Vec<_> = start.iter().map(|(x, y)| (x + 1, y + 1)).collect();
+        assert_eq!(live_cells(harness.state()), moved);
+    }
+
+    #[test]
+    fn clicked_cell_is_simulated_on_a_non_square_grid() {
+        let mut harness = Harness::<State>::new();
+        harness.ctx_mut().run_command("resize 3 5");
+        assert_eq!((harness.state().rows, harness.state().cols), (3, 5));
+
+        harness.tap_key(KeyCode::Space);
+        assert!(harness.state().drawing_mode);
+        // a horizontal blinker in the middle row
+        let grid = grid_rect();
+        let cell = vec2(grid.w / 5.0, grid.h / 3.0);
+        for col in 1..4 {
+            let center = grid.point() + (vec2(col as f32, 1.0) + 0.5) * cell;
+            let position = harness.ctx().world_to_screen(center);
+            harness.click(position);
+        }
+        assert_eq!(live_cells(harness.state()), [(1, 1), (2, 1), (3, 1)]);
+
+        harness.tap_key(KeyCode::Space);
+        harness.state_mut().update_cells();
+        assert_eq!(live_cells(harness.state()), [(2, 0), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn help_names_the_rebound_keys() {
+        let mut harness = Harness::<State>::new();
+        harness.ctx_mut().bind_action(TOGGLE_PAUSE, &[KeyCode::K]);
+        harness.step();
+        assert_eq!(harness.state().keys(TOGGLE_PAUSE), "K");
+        assert_eq!(harness.state().keys(TOGGLE_DRAWING), "Space");
+    }
+
+    #[test]
+    fn tab_gives_left_right_to_the_slider_and_back() {
+        let mut harness = Harness::<State>::new();
+        harness.step();
+        harness.tap_key(KeyCode::Tab);
+        harness.tap_key(KeyCode::Right);
+        assert_eq!(harness.state().cols, START_SIZE);
+        assert!(harness.state().step_time > 0.5);
+        // saved in the first frame without a change
+        let step_time = harness.state().step_time;
+        assert_eq!(harness.ctx_mut().storage().get::<f32>("step_time"), None);
+        harness.step();
+        assert_eq!(
+            harness.ctx_mut().storage().get("step_time"),
+            Some(step_time)
+        );
+
+        harness.tap_key(KeyCode::Tab);
+        harness.tap_key(KeyCode::Right);
+        assert_eq!(harness.state().cols, START_SIZE + 1);
+    }
+}
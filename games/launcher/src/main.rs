@@ -0,0 +1,10 @@
+use macroquad_stuff::Launcher;
+
+#[macroquad::main("Games")]
+async fn main() {
+    Launcher::new()
+        .add::<pong::State>()
+        .add::<convay::State>()
+        .run()
+        .await;
+}
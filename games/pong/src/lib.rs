@@ -0,0 +1,405 @@
+use std::f32::consts::PI;
+
+use macroquad::{prelude::*, rand};
+use macroquad_stuff::{
+    Context, Emitter, EmitterShape, GameInfo, GameState, GamepadAxis, GamepadButton,
+    ParticleSystem, SETTING_DEBUG_OVERLAY, ScaleMode, Scene, SoundEffect, TEXT_HEIGHT, Viewport,
+    Waveform, draw_text_centered,
+};
+
+const BALL_SPEED: f32 = 0.3;
+const BALL_SIZE: f32 = 0.01;
+const PADDLE_SPEED: f32 = 0.5;
+const PADDLE_HEIGHT: f32 = 0.1;
+/// Physics updates per second, independent of the frame rate
+const TICK_RATE: f32 = 120.0;
+/// Size of the field in world units, positions are fractions of it
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 600.0;
+const PADDLE_WIDTH: f32 = 10.0;
+
+const LEFT_UP: &str = "paddle_left_up";
+const LEFT_DOWN: &str = "paddle_left_down";
+const RIGHT_UP: &str = "paddle_right_up";
+const RIGHT_DOWN: &str = "paddle_right_down";
+
+const SOUND_PADDLE: &str = "paddle";
+const SOUND_WALL: &str = "wall";
+const SOUND_SCORE: &str = "score";
+
+const TEXT_TIME: f32 = 1.0;
+/// Points that win the game
+const WIN_SCORE: u32 = 5;
+const BLINK_TIME: f32 = 0.1;
+
+pub struct State {
+    text: String,
+    /// Seconds the text is still shown
+    text_timer: f32,
+    score_l: u32,
+    score_r: u32,
+    ball: Vec2,
+    ball_speed: Vec2,
+    paddle_l: f32,
+    paddle_r: f32,
+    /// Positions before the last update, to interpolate between updates when drawing
+    previous_ball: Vec2,
+    previous_paddle_l: f32,
+    previous_paddle_r: f32,
+    /// Seconds the paddles still blink
+    blink_l: f32,
+    blink_r: f32,
+    /// Sparks of paddle hits and explosions of points, in world units
+    particles: ParticleSystem,
+}
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            text_timer: 0.0,
+            score_l: 0,
+            score_r: 0,
+            ball: Vec2::new(0.5, 0.5),
+            ball_speed: get_random_speed(),
+            paddle_l: 0.5 - PADDLE_HEIGHT / 2.0,
+            paddle_r: 0.5 - PADDLE_HEIGHT / 2.0,
+            previous_ball: Vec2::new(0.5, 0.5),
+            previous_paddle_l: 0.5 - PADDLE_HEIGHT / 2.0,
+            previous_paddle_r: 0.5 - PADDLE_HEIGHT / 2.0,
+            blink_l: 0.0,
+            blink_r: 0.0,
+            particles: ParticleSystem::default(),
+        }
+    }
+}
+impl GameInfo for State {
+    fn name() -> &'static str {
+        "Pong"
+    }
+
+    fn description() -> &'static str {
+        "Two paddles, one ball"
+    }
+}
+
+impl GameState for State {
+    fn bg_color(&self) -> Color {
+        BLACK
+    }
+
+    fn tick_rate() -> Option<f32> {
+        Some(TICK_RATE)
+    }
+
+    fn viewport() -> Option<Viewport> {
+        Some(Viewport::new(WIDTH, HEIGHT, ScaleMode::Fit))
+    }
+
+    fn init(ctx: &mut Context<Self>) {
+        ctx.define_action(LEFT_UP, &[KeyCode::W]);
+        ctx.define_action(LEFT_DOWN, &[KeyCode::S]);
+        ctx.define_action(RIGHT_UP, &[KeyCode::Up]);
+        ctx.define_action(RIGHT_DOWN, &[KeyCode::Down]);
+        // the first gamepad plays left, the second one right
+        ctx.bind_action_buttons(LEFT_UP, Some(0), &[GamepadButton::DPadUp]);
+        ctx.bind_action_buttons(LEFT_DOWN, Some(0), &[GamepadButton::DPadDown]);
+        ctx.bind_action_buttons(RIGHT_UP, Some(1), &[GamepadButton::DPadUp]);
+        ctx.bind_action_buttons(RIGHT_DOWN, Some(1), &[GamepadButton::DPadDown]);
+        ctx.add_virtual_button(LEFT_UP, "^", Rect::new(0.02, 0.55, 0.1, 0.2));
+        ctx.add_virtual_button(LEFT_DOWN, "v", Rect::new(0.02, 0.78, 0.1, 0.2));
+        ctx.add_virtual_button(RIGHT_UP, "^", Rect::new(0.88, 0.55, 0.1, 0.2));
+        ctx.add_virtual_button(RIGHT_DOWN, "v", Rect::new(0.88, 0.78, 0.1, 0.2));
+        let paddle = SoundEffect::new(Waveform::Square, 440.0)
+            .slide_to(880.0)
+            .duration(0.08);
+        ctx.add_sound(SOUND_PADDLE, "effects", paddle);
+        let wall = SoundEffect::new(Waveform::Square, 220.0).duration(0.05);
+        ctx.add_sound(SOUND_WALL, "effects", wall);
+        let score = SoundEffect::new(Waveform::Saw, 660.0)
+            .slide_to(110.0)
+            .duration(0.4);
+        ctx.add_sound(SOUND_SCORE, "effects", score);
+        ctx.add_command("score", "<left> <right>", |ctx, args| {
+            ctx.state.score_l = args.get(0)?;
+            ctx.state.score_r = args.get(1)?;
+            Ok(())
+        });
+        ctx.add_command("ballspeed", "<speed>", |ctx, args| {
+            let speed: f32 = args.get(0)?;
+            if speed <= 0.0 {
+                return Err("the speed has to be positive".to_string());
+            }
+            ctx.state.ball_speed = ctx.state.ball_speed.normalize_or_zero() * speed;
+            Ok(())
+        });
+    }
+
+    async fn update(ctx: &mut Context<Self>, delta_time: f32) {
+        ctx.state.remember_positions();
+        ctx.state.tick_timers(delta_time);
+        ctx.state.particles.update(delta_time);
+        if let Some(sound) = check_points(&mut ctx.state) {
+            ctx.play_sound(sound);
+            let state = &ctx.state;
+            if sound == SOUND_SCORE && state.score_l.max(state.score_r) >= WIN_SCORE {
+                ctx.push_scene(GameOverScene {
+                    left_won: state.score_l > state.score_r,
+                });
+            }
+        }
+        update_positions(ctx, delta_time);
+        if ctx.setting_bool(SETTING_DEBUG_OVERLAY) {
+            let velocity = ctx.state.ball_speed;
+            let velocity = format!("({:.2}, {:.2})", velocity.x, velocity.y);
+            ctx.debug_watch("ball velocity", velocity);
+        }
+    }
+
+    fn draw(&self, alpha: f32) {
+        let ball = self.previous_ball.lerp(self.ball, alpha);
+        let paddle_l = lerp(self.previous_paddle_l, self.paddle_l, alpha);
+        let paddle_r = lerp(self.previous_paddle_r, self.paddle_r, alpha);
+
+        draw_text(&format!("Left: {}", self.score_l), 10.0, 20.0, 20.0, WHITE);
+        draw_text(
+            &format!("Right: {}", self.score_r),
+            WIDTH - 100.0,
+            20.0,
+            20.0,
+            WHITE,
+        );
+
+        if self.text_timer > 0.0 {
+            draw_text(&self.text, WIDTH / 2.0 - 100.0, HEIGHT / 2.0, 20.0, WHITE);
+        } else {
+            draw_circle(ball.x * WIDTH, ball.y * HEIGHT, BALL_SIZE * HEIGHT, WHITE);
+        }
+
+        self.particles.draw();
+
+        let paddle_height = PADDLE_HEIGHT * HEIGHT;
+        let left_color = if self.blink_l > 0.0 { GREEN } else { WHITE };
+        draw_rectangle(
+            0.0,
+            paddle_l * HEIGHT,
+            PADDLE_WIDTH,
+            paddle_height,
+            left_color,
+        );
+        let right_color = if self.blink_r > 0.0 { GREEN } else { WHITE };
+        draw_rectangle(
+            WIDTH - PADDLE_WIDTH,
+            paddle_r * HEIGHT,
+            PADDLE_WIDTH,
+            paddle_height,
+            right_color,
+        );
+    }
+
+    /// The game waits while the text of the last point is shown
+    fn is_paused(&self) -> bool {
+        self.text_timer > 0.0
+    }
+
+    async fn update_paused(ctx: &mut Context<Self>, delta_time: f32) {
+        ctx.state.tick_timers(delta_time);
+        ctx.state.particles.update(delta_time);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl State {
+    fn tick_timers(&mut self, delta: f32) {
+        self.text_timer = (self.text_timer - delta).max(0.0);
+        self.blink_l = (self.blink_l - delta).max(0.0);
+        self.blink_r = (self.blink_r - delta).max(0.0);
+    }
+
+    fn remember_positions(&mut self) {
+        self.previous_ball = self.ball;
+        self.previous_paddle_l = self.paddle_l;
+        self.previous_paddle_r = self.paddle_r;
+    }
+}
+
+fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
+    from + (to - from) * alpha
+}
+
+fn update_positions(ctx: &mut Context<State>, delta: f32) {
+    let left_up = ctx.is_action_down(LEFT_UP);
+    let left_down = ctx.is_action_down(LEFT_DOWN);
+    let right_up = ctx.is_action_down(RIGHT_UP);
+    let right_down = ctx.is_action_down(RIGHT_DOWN);
+    let stick = |n| {
+        ctx.nth_gamepad(n)
+            .map_or(0.0, |pad| ctx.gamepad_axis(pad, GamepadAxis::LeftY))
+    };
+    let (stick_l, stick_r) = (stick(0), stick(1));
+    let state = &mut ctx.state;
+    let bounced = state.ball.y < 0.0 || state.ball.y > 1.0;
+    if bounced {
+        state.ball_speed.y *= -1.0;
+    }
+    state.ball += state.ball_speed * delta;
+    if left_up {
+        state.paddle_l -= PADDLE_SPEED * delta;
+    }
+    if left_down {
+        state.paddle_l += PADDLE_SPEED * delta;
+    }
+    state.paddle_l += stick_l * PADDLE_SPEED * delta;
+    state.paddle_l = clamp(state.paddle_l, 0.0, 1.0 - PADDLE_HEIGHT);
+    if right_up {
+        state.paddle_r -= PADDLE_SPEED * delta;
+    }
+    if right_down {
+        state.paddle_r += PADDLE_SPEED * delta;
+    }
+    state.paddle_r += stick_r * PADDLE_SPEED * delta;
+    state.paddle_r = clamp(state.paddle_r, 0.0, 1.0 - PADDLE_HEIGHT);
+    if bounced {
+        ctx.play_sound(SOUND_WALL);
+    }
+}
+
+/// Returns the sound to play for a hit or a point
+fn check_points(state: &mut State) -> Option<&'static str> {
+    if state.ball.x < 0.0 {
+        if state.ball.y > state.paddle_l && state.ball.y < state.paddle_l + PADDLE_HEIGHT {
+            state.ball_speed.x *= -1.0;
+            state.blink_l = BLINK_TIME;
+            let position = vec2(PADDLE_WIDTH, state.ball.y * HEIGHT);
+            state.particles.burst(&sparks(0.0), position, 20);
+            Some(SOUND_PADDLE)
+        } else {
+            // right player scores
+            score(state, false);
+            Some(SOUND_SCORE)
+        }
+    } else if state.ball.x > 1.0 {
+        if state.ball.y > state.paddle_r && state.ball.y < state.paddle_r + PADDLE_HEIGHT {
+            state.ball_speed.x *= -1.0;
+            state.blink_r = BLINK_TIME;
+            let position = vec2(WIDTH - PADDLE_WIDTH, state.ball.y * HEIGHT);
+            state.particles.burst(&sparks(PI), position, 20);
+            Some(SOUND_PADDLE)
+        } else {
+            // left player scores
+            score(state, true);
+            Some(SOUND_SCORE)
+        }
+    } else {
+        None
+    }
+}
+
+fn score(state: &mut State, left: bool) {
+    if left {
+        state.score_l += 1;
+        state.text = "Left player scores!".to_string();
+    } else {
+        state.score_r += 1;
+        state.text = "Right player scores!".to_string();
+    }
+    state.text_timer = TEXT_TIME;
+    let position = state.ball.clamp(Vec2::ZERO, Vec2::ONE) * vec2(WIDTH, HEIGHT);
+    state.particles.burst(&explosion(), position, 150);
+    state.ball = Vec2::new(0.5, 0.5);
+    state.ball_speed = get_random_speed();
+    state.paddle_l = 0.5 - PADDLE_HEIGHT / 2.0;
+    state.paddle_r = 0.5 - PADDLE_HEIGHT / 2.0;
+    // don't interpolate the jump back to the middle
+    state.remember_positions();
+}
+
+/// Sparks flying off a paddle towards `direction` in radians
+fn sparks(direction: f32) -> Emitter {
+    Emitter::new()
+        .speed(100.0, 400.0)
+        .direction(direction, PI / 3.0)
+        .lifetime(0.2, 0.5)
+        .gravity(vec2(0.0, 600.0))
+        .color(YELLOW, Color::new(1.0, 0.3, 0.0, 0.0))
+        .size(4.0, 1.0)
+}
+
+/// Explosion where the ball left the field
+fn explosion() -> Emitter {
+    Emitter::new()
+        .shape(EmitterShape::Circle {
+            radius: BALL_SIZE * HEIGHT,
+        })
+        .speed(50.0, 350.0)
+        .lifetime(0.4, 1.0)
+        .gravity(vec2(0.0, 200.0))
+        .color(WHITE, Color::new(1.0, 0.0, 0.0, 0.0))
+        .size(6.0, 2.0)
+}
+
+fn get_random_speed() -> Vec2 {
+    let x = rand::gen_range(-0.5, 0.5);
+    let y = rand::gen_range(-0.5, 0.5);
+    Vec2::new(x, y).normalize() * BALL_SPEED
+}
+
+/// Shown over the field when a player reaches [`WIN_SCORE`], starts a new game when closed
+struct GameOverScene {
+    left_won: bool,
+}
+
+impl Scene<State> for GameOverScene {
+    fn update(&mut self, ctx: &mut Context<State>, _delta_time: f32) {
+        let (w, h) = (ctx.screen_width(), ctx.screen_height());
+        let th = TEXT_HEIGHT * h;
+        let button = Rect::new(w * 0.35, h / 2.0 + th, w * 0.3, th * 1.5);
+        ctx.set_ui_focus(Some(0));
+        if ctx.ui_button(button, "Play again") || ctx.is_key_pressed(KeyCode::Space) {
+            ctx.state.reset();
+            ctx.pop_scene();
+        }
+    }
+
+    fn draw(&self, ctx: &Context<State>) {
+        let (w, h) = (ctx.screen_width(), ctx.screen_height());
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
+        let winner = if self.left_won { "Left" } else { "Right" };
+        let text = format!("{winner} player wins!");
+        draw_text_centered(
+            &text,
+            w / 2.0,
+            h / 2.0 - TEXT_HEIGHT * h,
+            TEXT_HEIGHT * h * 1.5,
+            WHITE,
+        );
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use macroquad_stuff::Harness;
+
+    use super::*;
+
+    #[test]
+    fn ball_hits_left_paddle_and_reflects() {
+        let mut harness = Harness::<State>::new();
+        let state = harness.state_mut();
+        state.ball = vec2(0.05, state.paddle_l + PADDLE_HEIGHT / 2.0);
+        state.ball_speed = vec2(-BALL_SPEED, 0.0);
+        state.remember_positions();
+
+        assert!(harness.run_until(60, |state| state.ball_speed.x > 0.0));
+        let state = harness.state();
+        assert!(state.blink_l > 0.0);
+        assert_eq!((state.score_l, state.score_r), (0, 0));
+        assert_eq!(state.text_timer, 0.0);
+    }
+}
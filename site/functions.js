@@ -1,33 +1,148 @@
 register_plugin = function(importObject) {
     // make perform_demo() function available to call from rust
     importObject.env.open_file_js = open_file;
+    importObject.env.save_file_js = save_file;
+    importObject.env.storage_get_js = storage_get;
+    importObject.env.storage_set_js = storage_set;
+    importObject.env.storage_remove_js = storage_remove;
+    importObject.env.storage_keys_js = storage_keys;
+    importObject.env.gamepads_js = gamepads;
 }
 miniquad_add_plugin({ register_plugin })
 
-async function fileToText(file) {
+// payloads understood by js_response in src/js_bridge.rs
+const Payload = {
+    empty: () => ({ kind: 'empty' }),
+    string: (value) => ({ kind: 'string', value }),
+    bytes: (value, name) => ({ kind: 'bytes', value, name }),
+    number: (value) => ({ kind: 'number', value }),
+    error: (err) => ({ kind: 'error', value: String(err) }),
+    list: (value) => ({ kind: 'list', value }),
+};
+
+// answer the rust request with the given id
+function respond(id, payload) {
+    wasm_exports.js_response(id, js_object(payload));
+}
+
+async function fileToBytes(file) {
     const reader = new FileReader();
     return new Promise((resolve, reject) => {
-        reader.onload = () => resolve(reader.result);
-        reader.onerror = reject;
-        reader.readAsText(file);
+        reader.onload = () => resolve(new Uint8Array(reader.result));
+        reader.onerror = () => reject(reader.error);
+        reader.readAsArrayBuffer(file);
     });
 }
 
 
-async function open_file() {
+async function open_file(id, options_obj) {
+    const options = consume_js_object(options_obj);
     const input = document.createElement('input');
     input.type = 'file';
-    const text = await new Promise((resolve) => {
+    input.accept = options.accept;
+    input.multiple = options.multiple !== 0;
+    const response = await new Promise((resolve) => {
+        let done = false;
+        const finish = (response) => {
+            if (done) {
+                return;
+            }
+            done = true;
+            window.removeEventListener('focus', onFocus);
+            resolve(response);
+        };
+        // browsers without the cancel event only give focus back to the page,
+        // so wait a bit for a late change event before treating it as cancelled
+        const onFocus = () => {
+            setTimeout(() => {
+                if (!input.files || input.files.length === 0) {
+                    finish(Payload.empty());
+                }
+            }, 500);
+        };
+        input.addEventListener('cancel', () => finish(Payload.empty()));
         input.onchange = async e => {
             const target = e.target;
 
             if (!target.files || target.files.length === 0) {
+                finish(Payload.empty());
                 return;
             }
-            const text = await fileToText(target.files[0]);
-            resolve(text);
+            try {
+                const files = [];
+                for (const file of target.files) {
+                    const data = await fileToBytes(file);
+                    files.push(Payload.bytes(data, file.name));
+                }
+                finish(Payload.list(files));
+            } catch (err) {
+                finish(Payload.error(err));
+            }
         }
         input.click();
+        // the dialog takes focus away from the page, only listen once that happened
+        setTimeout(() => window.addEventListener('focus', onFocus), 0);
     });
-    wasm_exports.string_response(js_object(text));
+    respond(id, response);
+}
+
+function save_file(name_obj, data_obj) {
+    const name = consume_js_object(name_obj);
+    const data = consume_js_object(data_obj);
+    const url = URL.createObjectURL(new Blob([data]));
+    const a = document.createElement('a');
+    a.href = url;
+    a.download = name;
+    a.click();
+    // give the browser time to start the download before freeing the blob
+    setTimeout(() => URL.revokeObjectURL(url), 1000);
+}
+
+function storage_get(key_obj) {
+    const key = consume_js_object(key_obj);
+    return js_object(window.localStorage.getItem(key));
+}
+
+function storage_set(key_obj, value_obj) {
+    const key = consume_js_object(key_obj);
+    const value = consume_js_object(value_obj);
+    window.localStorage.setItem(key, value);
+}
+
+function storage_remove(key_obj) {
+    const key = consume_js_object(key_obj);
+    window.localStorage.removeItem(key);
+}
+
+// keys stored under "<name>/", without the prefix and one per line
+function storage_keys(name_obj) {
+    const prefix = consume_js_object(name_obj) + '/';
+    const keys = [];
+    for (let i = 0; i < window.localStorage.length; i++) {
+        const key = window.localStorage.key(i);
+        if (key.startsWith(prefix)) {
+            keys.push(key.slice(prefix.length));
+        }
+    }
+    return js_object(keys.join('\n'));
+}
+
+// connected gamepads in the standard mapping, read by src/gamepad.rs
+function gamepads() {
+    const pads = [];
+    const all = navigator.getGamepads ? navigator.getGamepads() : [];
+    for (const pad of all) {
+        if (!pad || !pad.connected) {
+            continue;
+        }
+        const buttons = Array.from(pad.buttons, (button) => button.pressed ? 1 : 0);
+        const value = (i) => pad.buttons[i] ? pad.buttons[i].value : 0;
+        const axis = (i) => pad.axes[i] || 0;
+        pads.push({
+            index: pad.index,
+            buttons,
+            axes: [axis(0), axis(1), axis(2), axis(3), value(6), value(7)],
+        });
+    }
+    return js_object(pads);
 }
@@ -5,23 +5,17 @@ SCRIPT_DIR=$( cd -- "$( dirname -- "${BASH_SOURCE[0]}" )" &> /dev/null && pwd )
 SITE_DIR=$(realpath "$SCRIPT_DIR/../_site")
 cd "$SCRIPT_DIR"
 
-list=""
+# the launcher hosts every game, so it is the only wasm to serve
+name="launcher"
 mkdir -p "$SITE_DIR"
-for dir in ../games/*; do
-    name=$(basename "$dir")
-    dir=$(realpath "$dir")
-    echo "Generating $name from $dir"
-    cd "$dir"
-    cargo build --release --target wasm32-unknown-unknown
-    list+="        <li><a href=\"$name.html\">$name</a></li>\n"
-    cd "$SITE_DIR"
-    cp "../target/wasm32-unknown-unknown/release/$name.wasm" "$name.wasm"
-    cp "$SCRIPT_DIR/game.html.template" "$name.html"
-    sed -i "s|GAME_NAME|$name|" "$name.html"
-done
+echo "Generating $name"
+cd "$SCRIPT_DIR/../games/$name"
+cargo build --release --target wasm32-unknown-unknown
+cd "$SITE_DIR"
+cp "../target/wasm32-unknown-unknown/release/$name.wasm" "$name.wasm"
 
 echo "Generating index.html"
 cp "$SCRIPT_DIR/index.html.template" index.html
-sed -i "s|LIST_ITEMS|$list|" index.html
+sed -i "s|WASM_NAME|$name|" index.html
 cp "$SCRIPT_DIR/style.css" style.css
 cp "$SCRIPT_DIR/functions.js" functions.js
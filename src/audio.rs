@@ -0,0 +1,305 @@
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use macroquad::{
+    audio::{PlaySoundParams, Sound, load_sound_from_bytes, play_sound},
+    prelude::*,
+};
+
+use crate::{Context, GameState, SETTING_VOLUME};
+
+const SAMPLE_RATE: u32 = 44100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Saw,
+    Triangle,
+    Noise,
+}
+
+/// Volume over the duration of a sound: it rises to full volume in `attack` seconds,
+/// falls to the `sustain` level in `decay` seconds, holds it and fades out in the last
+/// `release` seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Short attack and release without clicks, for beeps
+    pub const DEFAULT: Self = Self {
+        attack: 0.005,
+        decay: 0.05,
+        sustain: 0.7,
+        release: 0.05,
+    };
+
+    fn level(&self, t: f32, duration: f32) -> f32 {
+        let level = if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.decay {
+            let progress = (t - self.attack) / self.decay;
+            1.0 - (1.0 - self.sustain) * progress
+        } else {
+            self.sustain
+        };
+        let release_start = duration - self.release;
+        if t > release_start && self.release > 0.0 {
+            level * (1.0 - (t - release_start) / self.release).max(0.0)
+        } else {
+            level
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Retro sound effect synthesized from a waveform, no asset file needed.
+///
+/// ```ignore
+/// let hit = SoundEffect::new(Waveform::Square, 440.0).slide_to(660.0).duration(0.08);
+/// ctx.add_sound("hit", "effects", hit);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundEffect {
+    waveform: Waveform,
+    frequency: f32,
+    /// Frequency at the end, the pitch slides there over the duration
+    end_frequency: f32,
+    duration: f32,
+    envelope: Envelope,
+    volume: f32,
+}
+
+impl SoundEffect {
+    /// A sound of 0.1 seconds at `frequency` Hz
+    #[must_use]
+    pub fn new(waveform: Waveform, frequency: f32) -> Self {
+        Self {
+            waveform,
+            frequency,
+            end_frequency: frequency,
+            duration: 0.1,
+            envelope: Envelope::DEFAULT,
+            volume: 0.5,
+        }
+    }
+
+    #[must_use]
+    pub fn slide_to(mut self, frequency: f32) -> Self {
+        self.end_frequency = frequency;
+        self
+    }
+
+    /// Length in seconds
+    #[must_use]
+    pub fn duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    #[must_use]
+    pub fn envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Volume from 0 to 1 before the master and channel volume
+    #[must_use]
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    fn samples(&self) -> Vec<f32> {
+        let count = (self.duration * SAMPLE_RATE as f32) as usize;
+        let mut phase = 0.0_f32;
+        // own noise generator, so sounds don't change the random numbers of the game
+        let mut noise = 0x2545_f491_u32;
+        let mut noise_value = 0.0;
+        (0..count)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let progress = t / self.duration;
+                // exponential slide sounds even across octaves
+                let frequency =
+                    self.frequency * (self.end_frequency / self.frequency).powf(progress);
+                let previous_phase = phase;
+                phase = (phase + frequency / SAMPLE_RATE as f32).fract();
+                let value = match self.waveform {
+                    Waveform::Square => {
+                        if phase < 0.5 {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                    Waveform::Saw => phase * 2.0 - 1.0,
+                    Waveform::Triangle => 1.0 - (phase * 4.0 - 2.0).abs(),
+                    Waveform::Noise => {
+                        // a new random value every period keeps the pitch of the noise
+                        if phase < previous_phase || i == 0 {
+                            noise ^= noise << 13;
+                            noise ^= noise >> 17;
+                            noise ^= noise << 5;
+                            noise_value = noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                        }
+                        noise_value
+                    }
+                };
+                value * self.envelope.level(t, self.duration) * self.volume
+            })
+            .collect()
+    }
+
+    /// Encodes the samples as 16 bit mono WAV, which macroquad can load
+    fn to_wav(self) -> Vec<u8> {
+        let samples = self.samples();
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16_u32.to_le_bytes());
+        // PCM, one channel
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+        wav.extend_from_slice(&2_u16.to_le_bytes());
+        wav.extend_from_slice(&16_u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+            wav.extend_from_slice(&value.to_le_bytes());
+        }
+        wav
+    }
+}
+
+struct Entry {
+    channel: String,
+    /// Hash of the data, to skip adding the same sound again, e.g. when `init` runs again
+    source: u64,
+    /// The effect the data was synthesized from, to skip synthesizing it again
+    effect: Option<SoundEffect>,
+    /// WAV or OGG data, dropped once the sound is loaded
+    data: Vec<u8>,
+    sound: Option<Sound>,
+}
+
+/// Named sounds in channels with their own volume
+#[derive(Default)]
+pub(crate) struct Audio {
+    sounds: BTreeMap<String, Entry>,
+    channels: BTreeMap<String, f32>,
+}
+
+impl Audio {
+    fn insert(&mut self, name: &str, channel: &str, data: Vec<u8>, effect: Option<SoundEffect>) {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let source = hasher.finish();
+        if self
+            .sounds
+            .get(name)
+            .is_some_and(|entry| entry.source == source)
+        {
+            self.set_channel(name, channel);
+            return;
+        }
+        // replacing the entry drops the old sound, which unloads it
+        let entry = Entry {
+            channel: channel.to_string(),
+            source,
+            effect,
+            data,
+            sound: None,
+        };
+        self.sounds.insert(name.to_string(), entry);
+    }
+
+    fn set_channel(&mut self, name: &str, channel: &str) {
+        if let Some(entry) = self.sounds.get_mut(name) {
+            entry.channel = channel.to_string();
+        }
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Adds a synthesized sound that can be played by name.
+    /// Sounds are loaded by the game loop, so this can be called in [`GameState::init`].
+    pub fn add_sound(&mut self, name: &str, channel: &str, effect: SoundEffect) {
+        if let Some(entry) = self.audio.sounds.get(name)
+            && entry.effect == Some(effect)
+        {
+            self.audio.set_channel(name, channel);
+            return;
+        }
+        self.audio
+            .insert(name, channel, effect.to_wav(), Some(effect));
+    }
+
+    /// Adds a sound from the bytes of a WAV or OGG file.
+    /// Adding the same data under the same name again keeps the loaded sound.
+    pub fn add_sound_data(&mut self, name: &str, channel: &str, data: Vec<u8>) {
+        self.audio.insert(name, channel, data, None);
+    }
+
+    /// Plays the sound once at the master volume times the volume of its channel.
+    /// Sounds that are not loaded yet are skipped.
+    pub fn play_sound(&self, name: &str) {
+        let Some(entry) = self.audio.sounds.get(name) else {
+            warn!("Unknown sound {}", name);
+            return;
+        };
+        let volume = self.setting_value(SETTING_VOLUME) * self.channel_volume(&entry.channel);
+        if let Some(sound) = &entry.sound
+            && volume > 0.0
+        {
+            let params = PlaySoundParams {
+                looped: false,
+                volume,
+            };
+            play_sound(sound, params);
+        }
+    }
+
+    /// Volume of the channel from 0 to 1, 1 by default
+    #[must_use]
+    pub fn channel_volume(&self, channel: &str) -> f32 {
+        self.audio.channels.get(channel).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_channel_volume(&mut self, channel: &str, volume: f32) {
+        self.audio
+            .channels
+            .insert(channel.to_string(), volume.clamp(0.0, 1.0));
+    }
+
+    /// Loads the sounds added since the last call, called by the game loop every frame
+    pub(crate) async fn load_sounds(&mut self) {
+        for (name, entry) in &mut self.audio.sounds {
+            // data is empty once loaded or failed
+            if entry.data.is_empty() {
+                continue;
+            }
+            match load_sound_from_bytes(&entry.data).await {
+                Ok(sound) => entry.sound = Some(sound),
+                Err(e) => warn!("Failed to load sound {}: {}", name, e),
+            }
+            entry.data = Vec::new();
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use macroquad::prelude::*;
+
+use crate::{Context, GameState};
+
+/// GIF frames per second, each frame is shown for `100 / GIF_FPS` hundredths of a second
+const GIF_FPS: u16 = 20;
+/// GIF frames are scaled down to at most this width to keep the files small
+const GIF_MAX_WIDTH: u16 = 480;
+/// A GIF capture stops and is saved by itself after this many seconds
+const GIF_MAX_SECONDS: f64 = 30.0;
+
+/// A GIF being captured, the frames are encoded as they come in
+struct GifCapture {
+    encoder: gif::Encoder<Vec<u8>>,
+    width: u16,
+    height: u16,
+    started: f64,
+    next_frame: f64,
+    frames: u32,
+    /// Set when the capture is stopped, it is saved at the end of the frame
+    finished: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct Capture {
+    screenshot: bool,
+    gif: Option<GifCapture>,
+}
+
+/// Copies the screen into `width`x`height` RGBA pixels, top row first and fully opaque.
+/// Pixels are picked from the nearest screen pixel, so this also scales the screen.
+fn screen_pixels(screen: &Image, width: u16, height: u16) -> Vec<u8> {
+    let (screen_w, screen_h) = (usize::from(screen.width), usize::from(screen.height));
+    let (width, height) = (usize::from(width), usize::from(height));
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        // the screen data starts with the bottom row
+        let source_y = screen_h - 1 - y * screen_h / height;
+        for x in 0..width {
+            let source_x = x * screen_w / width;
+            let i = (source_y * screen_w + source_x) * 4;
+            pixels.extend_from_slice(&screen.bytes[i..i + 3]);
+            pixels.push(255);
+        }
+    }
+    pixels
+}
+
+fn encode_png(pixels: &[u8], width: u16, height: u16) -> Result<Vec<u8>, png::EncodingError> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, u32::from(width), u32::from(height));
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(bytes)
+}
+
+impl<S: GameState> Context<S> {
+    /// Saves a PNG of the screen at the end of the frame.
+    /// On native the user picks where to save it, on wasm it is downloaded.
+    pub fn take_screenshot(&mut self) {
+        self.capture.screenshot = true;
+    }
+
+    /// Starts capturing the screen into a GIF, or stops and saves it if a capture is running.
+    /// Captures stop by themselves after 30 seconds.
+    pub fn toggle_gif_capture(&mut self) {
+        if self.capture.gif.is_some() {
+            self.stop_gif_capture();
+            return;
+        }
+        let scale = (self.screen_width() / f32::from(GIF_MAX_WIDTH)).max(1.0);
+        let width = (self.screen_width() / scale) as u16;
+        let height = (self.screen_height() / scale) as u16;
+        let encoder = gif::Encoder::new(Vec::new(), width, height, &[]).and_then(|mut encoder| {
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            Ok(encoder)
+        });
+        match encoder {
+            Ok(encoder) => {
+                info!("Capturing a {}x{} GIF", width, height);
+                self.capture.gif = Some(GifCapture {
+                    encoder,
+                    width,
+                    height,
+                    started: self.time(),
+                    next_frame: self.time(),
+                    frames: 0,
+                    finished: false,
+                });
+            }
+            Err(e) => warn!("Failed to start GIF capture: {}", e),
+        }
+    }
+
+    #[must_use]
+    pub fn is_capturing_gif(&self) -> bool {
+        self.capture.gif.is_some()
+    }
+
+    /// Ends the GIF capture, it is saved at the end of the frame
+    fn stop_gif_capture(&mut self) {
+        if let Some(gif) = &mut self.capture.gif {
+            gif.finished = true;
+        }
+    }
+
+    /// Grabs the drawn screen for a requested screenshot or the running GIF capture
+    /// and saves finished captures. Called by the game loop after drawing.
+    pub(crate) async fn capture_frame(&mut self) {
+        let now = self.time();
+        let gif_due = self
+            .capture
+            .gif
+            .as_ref()
+            .is_some_and(|gif| !gif.finished && now >= gif.next_frame);
+        if self.capture.screenshot || gif_due {
+            let screen = get_screen_data();
+            if gif_due {
+                self.capture_gif_frame(&screen, now);
+            }
+            if self.capture.screenshot {
+                self.capture.screenshot = false;
+                self.save_screenshot(&screen).await;
+            }
+        }
+        if self.capture.gif.as_ref().is_some_and(|gif| gif.finished) {
+            self.save_gif().await;
+        }
+    }
+
+    fn capture_gif_frame(&mut self, screen: &Image, now: f64) {
+        let Some(gif) = &mut self.capture.gif else {
+            return;
+        };
+        let mut pixels = screen_pixels(screen, gif.width, gif.height);
+        let mut frame = gif::Frame::from_rgba_speed(gif.width, gif.height, &mut pixels, 20);
+        frame.delay = 100 / GIF_FPS;
+        if let Err(e) = gif.encoder.write_frame(&frame) {
+            warn!("Failed to encode GIF frame: {}", e);
+            gif.finished = true;
+            return;
+        }
+        gif.frames += 1;
+        // after a hitch, continue from now instead of catching up
+        gif.next_frame = (gif.next_frame + 1.0 / f64::from(GIF_FPS)).max(now);
+        if now - gif.started >= GIF_MAX_SECONDS {
+            gif.finished = true;
+        }
+    }
+
+    async fn save_screenshot(&self, screen: &Image) {
+        let pixels = screen_pixels(screen, screen.width, screen.height);
+        match encode_png(&pixels, screen.width, screen.height) {
+            Ok(png) => {
+                let name = format!("{}-screenshot.png", S::storage_name());
+                if let Err(e) = self.save_file(&name, &png).await {
+                    warn!("Failed to save screenshot: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to encode screenshot: {}", e),
+        }
+    }
+
+    async fn save_gif(&mut self) {
+        let Some(gif) = self.capture.gif.take() else {
+            return;
+        };
+        if gif.frames == 0 {
+            return;
+        }
+        info!("Saving GIF of {} frames", gif.frames);
+        let bytes = match gif.encoder.into_inner() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to finish GIF: {}", e);
+                return;
+            }
+        };
+        let name = format!("{}-capture.gif", S::storage_name());
+        if let Err(e) = self.save_file(&name, &bytes).await {
+            warn!("Failed to save GIF: {}", e);
+        }
+    }
+}
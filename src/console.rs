@@ -0,0 +1,397 @@
+use std::str::FromStr;
+
+use macroquad::prelude::*;
+
+use crate::{
+    ACTION_CONSOLE, ACTION_PAUSE, Context, GameState, HAlign, TEXT_HEIGHT, TextLayout, VAlign,
+    scene::Scene,
+};
+
+/// Lines kept in the output, older ones are dropped
+const SCROLLBACK: usize = 500;
+const HISTORY: usize = 100;
+/// Part of the screen height the console covers
+const CONSOLE_HEIGHT: f32 = 0.45;
+/// Commands of every console, handled in [`Context::run_command`]
+const BUILTIN_COMMANDS: [&str; 2] = ["help", "clear"];
+
+/// Runs a console command, the error is shown in the console together with the usage
+pub type CommandFn<S> = fn(&mut Context<S>, &CommandArgs) -> Result<(), String>;
+
+/// Returns the values Tab completes the i-th argument of a command to
+pub type CompleteFn<S> = fn(&Context<S>, usize) -> Vec<String>;
+
+/// Arguments of a console command, split at spaces
+#[derive(Debug, Clone, Default)]
+pub struct CommandArgs {
+    args: Vec<String>,
+}
+
+impl CommandArgs {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// The i-th argument as text
+    #[must_use]
+    pub fn text(&self, i: usize) -> Option<&str> {
+        self.args.get(i).map(String::as_str)
+    }
+
+    /// Parses the i-th argument, with an error message if it is missing or invalid
+    pub fn get<T: FromStr>(&self, i: usize) -> Result<T, String> {
+        let arg = self
+            .text(i)
+            .ok_or_else(|| format!("missing argument {}", i + 1))?;
+        arg.parse().map_err(|_| {
+            let kind = std::any::type_name::<T>().rsplit("::").next().unwrap_or("");
+            format!("argument {}: expected {kind}, got '{arg}'", i + 1)
+        })
+    }
+
+    /// Parses the i-th argument, or returns `default` if there are fewer arguments
+    pub fn get_or<T: FromStr>(&self, i: usize, default: T) -> Result<T, String> {
+        if i < self.len() {
+            self.get(i)
+        } else {
+            Ok(default)
+        }
+    }
+}
+
+struct Command<S: GameState> {
+    name: String,
+    /// Arguments for the help, e.g. "<rows> <cols>"
+    usage: String,
+    run: CommandFn<S>,
+    complete: Option<CompleteFn<S>>,
+}
+
+impl<S: GameState> Clone for Command<S> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            usage: self.usage.clone(),
+            run: self.run,
+            complete: self.complete,
+        }
+    }
+}
+
+/// Commands, input and output of the console, kept while it is closed
+pub(crate) struct Console<S: GameState> {
+    commands: Vec<Command<S>>,
+    input: String,
+    history: Vec<String>,
+    /// Entry of the history shown in the input while going through it with Up/Down
+    history_index: Option<usize>,
+    output: Vec<(String, Color)>,
+    /// Lines scrolled up from the newest output
+    scroll: usize,
+}
+
+impl<S: GameState> Default for Console<S> {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            input: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            output: Vec::new(),
+            scroll: 0,
+        }
+    }
+}
+
+impl<S: GameState> Console<S> {
+    /// Removes the commands, they are added again by `init` after a restart
+    pub(crate) fn clear_commands(&mut self) {
+        self.commands.clear();
+    }
+
+    fn print(&mut self, text: &str, color: Color) {
+        for line in text.lines() {
+            self.output.push((line.to_string(), color));
+        }
+        if self.output.len() > SCROLLBACK {
+            self.output.drain(..self.output.len() - SCROLLBACK);
+        }
+        self.scroll = 0;
+    }
+
+    /// Completes `partial`, the last word of the input after `head`, as far as all matching
+    /// candidates agree, listing them if there are several
+    fn complete(&mut self, head: &str, partial: &str, candidates: &[String]) {
+        let matches: Vec<&String> = candidates
+            .iter()
+            .filter(|candidate| candidate.starts_with(partial))
+            .collect();
+        let Some(first) = matches.first() else {
+            return;
+        };
+        let mut common = (*first).clone();
+        for candidate in &matches[1..] {
+            while !candidate.starts_with(common.as_str()) {
+                common.pop();
+            }
+        }
+        if matches.len() == 1 {
+            common.push(' ');
+        } else if common == partial {
+            let list: Vec<&str> = matches.iter().map(|m| m.as_str()).collect();
+            self.print(&list.join("  "), GRAY);
+        }
+        self.input = format!("{head}{common}");
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.history_index = match (self.history_index, older) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_index
+            .map(|i| self.history[i].clone())
+            .unwrap_or_default();
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Adds a command to the console, replacing one with the same name.
+    /// `usage` describes the arguments for the help, e.g. `"<rows> <cols>"`.
+    ///
+    /// ```ignore
+    /// ctx.add_command("score", "<left> <right>", |ctx, args| {
+    ///     ctx.state.score_l = args.get(0)?;
+    ///     ctx.state.score_r = args.get(1)?;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn add_command(&mut self, name: &str, usage: &str, run: CommandFn<S>) {
+        let command = Command {
+            name: name.to_string(),
+            usage: usage.to_string(),
+            run,
+            complete: None,
+        };
+        let commands = &mut self.console.commands;
+        match commands.iter_mut().find(|c| c.name == name) {
+            Some(existing) => *existing = command,
+            None => commands.push(command),
+        }
+    }
+
+    /// Lets Tab complete the arguments of a command added with [`Context::add_command`]
+    ///
+    /// ```ignore
+    /// ctx.set_command_completion("rule", |_, _| vec!["B3/S23".into(), "B36/S23".into()]);
+    /// ```
+    pub fn set_command_completion(&mut self, name: &str, complete: CompleteFn<S>) {
+        if let Some(command) = self.console.commands.iter_mut().find(|c| c.name == name) {
+            command.complete = Some(complete);
+        }
+    }
+
+    /// Completes the command name or, with a completion set, the argument being typed
+    fn complete_input(&mut self) {
+        let input = self.console.input.clone();
+        let (head, partial) = input.split_at(input.rfind(' ').map_or(0, |i| i + 1));
+        let candidates: Vec<String> = if head.trim().is_empty() {
+            BUILTIN_COMMANDS
+                .iter()
+                .map(ToString::to_string)
+                .chain(self.console.commands.iter().map(|c| c.name.clone()))
+                .collect()
+        } else {
+            let mut words = head.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let index = words.count();
+            self.console
+                .commands
+                .iter()
+                .find(|c| c.name == name)
+                .and_then(|c| c.complete)
+                .map(|complete| complete(self, index))
+                .unwrap_or_default()
+        };
+        self.console.complete(head, partial, &candidates);
+    }
+
+    /// Adds a line to the console output
+    pub fn console_print(&mut self, text: &str) {
+        self.console.print(text, WHITE);
+    }
+
+    /// Runs a line as if it was typed into the console, printing it and any error
+    pub fn run_command(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.console.print(&format!("> {line}"), GRAY);
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args = CommandArgs {
+            args: words.map(ToString::to_string).collect(),
+        };
+        match name {
+            "help" => {
+                let help: Vec<String> = self
+                    .console
+                    .commands
+                    .iter()
+                    .map(|command| format!("{} {}", command.name, command.usage))
+                    .collect();
+                self.console_print("help: list the commands, clear: clear the output");
+                self.console_print(&help.join("\n"));
+            }
+            "clear" => self.console.output.clear(),
+            _ => {
+                let Some(command) = self.console.commands.iter().find(|c| c.name == name) else {
+                    let error = format!("unknown command '{name}', try help");
+                    self.console.print(&error, RED);
+                    return;
+                };
+                let command = command.clone();
+                if let Err(e) = (command.run)(self, &args) {
+                    self.console.print(&e, RED);
+                    let usage = format!("usage: {} {}", command.name, command.usage);
+                    self.console.print(&usage, GRAY);
+                }
+            }
+        }
+    }
+}
+
+/// Console dropping down from the top of the screen, opened and closed with the console action
+pub(crate) struct ConsoleScene;
+
+impl<S: GameState> Scene<S> for ConsoleScene {
+    fn update(&mut self, ctx: &mut Context<S>, _delta_time: f32) {
+        if ctx.is_action_pressed(ACTION_CONSOLE) || ctx.is_action_pressed(ACTION_PAUSE) {
+            ctx.pop_scene();
+            return;
+        }
+        let console_key: Vec<char> = ctx
+            .action_keys(ACTION_CONSOLE)
+            .iter()
+            .filter_map(|key| key_char(*key))
+            .collect();
+        let typed: String = ctx
+            .chars_typed()
+            .iter()
+            .filter(|c| !c.is_control() && !console_key.contains(c))
+            .collect();
+        if !typed.is_empty() {
+            ctx.console.input.push_str(&typed);
+            ctx.console.history_index = None;
+        }
+        if ctx.is_key_pressed_loop(KeyCode::Backspace) {
+            ctx.console.input.pop();
+        }
+        if ctx.is_key_pressed(KeyCode::Tab) {
+            ctx.complete_input();
+        }
+        if ctx.is_key_pressed(KeyCode::Up) {
+            ctx.console.browse_history(true);
+        }
+        if ctx.is_key_pressed(KeyCode::Down) {
+            ctx.console.browse_history(false);
+        }
+        let lines = ctx.console.output.len();
+        let page = visible_lines(ctx.screen_height());
+        if ctx.is_key_pressed_loop(KeyCode::PageUp) || ctx.mouse_wheel() > 0.0 {
+            ctx.console.scroll = (ctx.console.scroll + page / 2).min(lines.saturating_sub(page));
+        }
+        if ctx.is_key_pressed_loop(KeyCode::PageDown) || ctx.mouse_wheel() < 0.0 {
+            ctx.console.scroll = ctx.console.scroll.saturating_sub(page / 2);
+        }
+        if ctx.is_key_pressed(KeyCode::Enter) {
+            let line = std::mem::take(&mut ctx.console.input);
+            let console = &mut ctx.console;
+            console.history_index = None;
+            if !line.trim().is_empty() && console.history.last() != Some(&line) {
+                console.history.push(line.clone());
+                if console.history.len() > HISTORY {
+                    console.history.remove(0);
+                }
+            }
+            ctx.run_command(&line);
+        }
+    }
+
+    fn draw(&self, ctx: &Context<S>) {
+        let w = screen_width();
+        let h = screen_height() * CONSOLE_HEIGHT;
+        let th = TEXT_HEIGHT * screen_height() * 0.6;
+        let line = th * 1.1;
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.85));
+        draw_line(0.0, h, w, h, 2.0, GRAY);
+
+        // input at the bottom, output above it from the newest line up
+        let cursor = if (ctx.time() * 2.0).fract() < 0.5 {
+            "_"
+        } else {
+            ""
+        };
+        let text_line = |text: &str, color: Color, y: f32| {
+            TextLayout::new(text)
+                .size(th)
+                .color(color)
+                .align(HAlign::Left, VAlign::Bottom)
+                .draw(5.0, y);
+        };
+        let input = format!("> {}{cursor}", ctx.console.input);
+        text_line(&input, YELLOW, h - line * 0.3);
+        let console = &ctx.console;
+        let newest = console.output.len().saturating_sub(console.scroll);
+        let mut y = h - line * 1.3;
+        for (text, color) in console.output[..newest].iter().rev() {
+            if y < line {
+                break;
+            }
+            text_line(text, *color, y);
+            y -= line;
+        }
+        if console.scroll > 0 {
+            TextLayout::new(&format!("{} more lines below", console.scroll))
+                .size(th * 0.8)
+                .color(GRAY)
+                .align(HAlign::Right, VAlign::Top)
+                .draw(w - 5.0, 5.0);
+        }
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
+
+/// Number of output lines that fit into the console
+fn visible_lines(screen_height: f32) -> usize {
+    let line = TEXT_HEIGHT * screen_height * 0.6 * 1.1;
+    ((screen_height * CONSOLE_HEIGHT / line) as usize).saturating_sub(2)
+}
+
+/// Character a key types without modifiers, to keep the console key out of the input
+fn key_char(key: KeyCode) -> Option<char> {
+    let code = key as u32;
+    (0x20..0x7f)
+        .contains(&code)
+        .then(|| char::from_u32(code))
+        .flatten()
+        .map(|c| c.to_ascii_lowercase())
+}
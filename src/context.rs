@@ -3,7 +3,7 @@ use macroquad::window::next_frame;
 #[cfg(not(target_arch = "wasm32"))]
 use pollster::FutureExt;
 
-use crate::{Context, GameState};
+use crate::{Context, FileError, GameState, OpenOptions, OpenedFile};
 
 impl<S: GameState> Context<S> {
     #[cfg(not(target_arch = "wasm32"))]
@@ -22,20 +22,74 @@ impl<S: GameState> Context<S> {
                 return s;
             }
             // continue with drawing loop
-            self.state.draw();
+            self.draw_state();
             next_frame().await;
         }
     }
 
-    #[must_use]
-    pub async fn open_file(&self) -> String {
+    /// Lets the user pick a file matching `options` and reads it.
+    /// Returns `Ok(None)` if the dialog was cancelled.
+    pub async fn open_file_with(
+        &self,
+        options: &OpenOptions,
+    ) -> Result<Option<OpenedFile>, FileError> {
+        let dialog = file_dialog(options);
         self.block_on(async move {
-            let Some(file) = rfd::AsyncFileDialog::new().pick_file().await else {
-                return String::new();
+            let Some(file) = dialog.pick_file().await else {
+                return Ok(None);
             };
-            let data = file.read().await;
-            String::from_utf8_lossy(&data).to_string()
+            read_file(&file).map(Some)
         })
         .await
     }
+
+    /// Lets the user pick any number of files matching `options` and reads them.
+    /// Returns an empty list if the dialog was cancelled.
+    pub async fn open_files(&self, options: &OpenOptions) -> Result<Vec<OpenedFile>, FileError> {
+        let dialog = file_dialog(options);
+        self.block_on(async move {
+            let Some(files) = dialog.pick_files().await else {
+                return Ok(Vec::new());
+            };
+            files.iter().map(read_file).collect()
+        })
+        .await
+    }
+
+    /// Asks the user where to save `bytes`, suggesting `suggested_name`.
+    /// Returns `Ok(false)` if the dialog was cancelled.
+    pub async fn save_file(&self, suggested_name: &str, bytes: &[u8]) -> Result<bool, FileError> {
+        let name = suggested_name.to_string();
+        let bytes = bytes.to_vec();
+        self.block_on(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .set_file_name(name)
+                .save_file()
+                .await
+            else {
+                return Ok(false);
+            };
+            file.write(&bytes).await?;
+            Ok(true)
+        })
+        .await
+    }
+}
+
+fn file_dialog(options: &OpenOptions) -> rfd::AsyncFileDialog {
+    let mut dialog = rfd::AsyncFileDialog::new();
+    if let Some(title) = &options.title {
+        dialog = dialog.set_title(title);
+    }
+    for (name, extensions) in &options.filters {
+        dialog = dialog.add_filter(name, extensions);
+    }
+    dialog
+}
+
+fn read_file(file: &rfd::FileHandle) -> Result<OpenedFile, FileError> {
+    Ok(OpenedFile {
+        name: file.file_name(),
+        bytes: std::fs::read(file.path())?,
+    })
 }
@@ -1,42 +1,69 @@
-use crate::{Context, GameState};
+use crate::{Context, FileError, GameState, OpenOptions, OpenedFile, js_bridge::JsPayload};
 use macroquad::prelude::*;
 use sapp_jsutils::JsObject;
-use std::sync::{Mutex, mpsc::Sender};
-
-static STRING_RESPONSE: Mutex<Option<Sender<String>>> = Mutex::new(None);
 
 impl<S: GameState> Context<S> {
-    #[must_use]
-    pub async fn open_file(&self) -> String {
+    /// Lets the user pick a file matching `options` and reads it.
+    /// Returns `Ok(None)` if the dialog was cancelled.
+    pub async fn open_file_with(
+        &self,
+        options: &OpenOptions,
+    ) -> Result<Option<OpenedFile>, FileError> {
+        let files = self.pick_files(options, false).await?;
+        Ok(files.into_iter().next())
+    }
+
+    /// Lets the user pick any number of files matching `options` and reads them.
+    /// Returns an empty list if the dialog was cancelled.
+    pub async fn open_files(&self, options: &OpenOptions) -> Result<Vec<OpenedFile>, FileError> {
+        self.pick_files(options, true).await
+    }
+
+    async fn pick_files(
+        &self,
+        options: &OpenOptions,
+        multiple: bool,
+    ) -> Result<Vec<OpenedFile>, FileError> {
         info!("Opening file");
-        let (tx, rx) = std::sync::mpsc::channel();
-        STRING_RESPONSE.lock().unwrap().replace(tx);
-        unsafe { open_file_js() };
-        loop {
-            match rx.try_recv() {
-                Ok(s) => break s,
-                Err(_) => {
-                    self.state.draw();
-                    next_frame().await;
-                }
+        let js_options = JsObject::object();
+        js_options.set_field_string("accept", &options.accept());
+        js_options.set_field_u32("multiple", u32::from(multiple));
+        match self
+            .js_request(|id| unsafe { open_file_js(id, js_options) })
+            .await
+        {
+            JsPayload::Empty => {
+                info!("File selection cancelled");
+                Ok(Vec::new())
             }
+            JsPayload::List(files) => files.into_iter().map(to_opened_file).collect(),
+            other => to_opened_file(other).map(|file| vec![file]),
         }
     }
+
+    /// Starts a browser download of `bytes` named `suggested_name`.
+    /// Returns `Ok(true)` once the download was handed to the browser.
+    pub async fn save_file(&self, suggested_name: &str, bytes: &[u8]) -> Result<bool, FileError> {
+        info!("Saving file {}", suggested_name);
+        unsafe { save_file_js(JsObject::string(suggested_name), JsObject::buffer(bytes)) };
+        Ok(true)
+    }
 }
 
-unsafe extern "C" {
-    fn open_file_js() -> JsObject;
+fn to_opened_file(payload: JsPayload) -> Result<OpenedFile, FileError> {
+    match payload {
+        JsPayload::Bytes { name, data } => Ok(OpenedFile {
+            name: name.unwrap_or_default(),
+            bytes: data,
+        }),
+        JsPayload::Error(e) => Err(FileError::Browser(e)),
+        other => Err(FileError::Browser(format!(
+            "unexpected response: {other:?}"
+        ))),
+    }
 }
 
-#[unsafe(no_mangle)]
-pub extern "C" fn string_response(js_obj: JsObject) {
-    let mut lock = STRING_RESPONSE.lock().unwrap();
-    let Some(sender) = lock.take() else {
-        // This should never happen, but if it does, we just ignore the response
-        warn!("Received a response, but no sender was set");
-        return;
-    };
-    let mut text = String::new();
-    js_obj.to_string(&mut text);
-    sender.send(text).unwrap();
+unsafe extern "C" {
+    fn open_file_js(id: u32, options: JsObject);
+    fn save_file_js(name: JsObject, data: JsObject);
 }
@@ -0,0 +1,136 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, SETTING_DEBUG_OVERLAY, TEXT_HEIGHT};
+
+/// Number of frames shown in the graph
+const HISTORY: usize = 120;
+/// Frame time at the top of the graph, longer frames are cut off
+const GRAPH_MAX: f32 = 1.0 / 20.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+const BAR_WIDTH: f32 = 2.0;
+const UPDATE_COLOR: Color = SKYBLUE;
+const DRAW_COLOR: Color = ORANGE;
+
+#[derive(Debug, Clone, Copy)]
+struct Timing {
+    frame: f32,
+    update: f32,
+    draw: f32,
+}
+
+/// Timings of the last frames and the values the game watches
+#[derive(Debug, Default)]
+pub(crate) struct DebugOverlay {
+    timings: VecDeque<Timing>,
+    /// Name and value in the order they were first watched
+    watches: Vec<(String, String)>,
+}
+
+impl DebugOverlay {
+    fn average(&self, value: impl Fn(&Timing) -> f32) -> f32 {
+        if self.timings.is_empty() {
+            return 0.0;
+        }
+        self.timings.iter().map(value).sum::<f32>() / self.timings.len() as f32
+    }
+}
+
+/// Color of a frame bar by the frame rate it reaches
+fn budget_color(frame: f32) -> Color {
+    if frame <= 1.0 / 55.0 {
+        GREEN
+    } else if frame <= 1.0 / 28.0 {
+        YELLOW
+    } else {
+        RED
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Shows `value` under `name` in the debug overlay until it is watched again with a new value.
+    /// Does nothing while the overlay is hidden, values that are expensive to compute
+    /// should only be computed if [`SETTING_DEBUG_OVERLAY`] is on.
+    pub fn debug_watch(&mut self, name: &str, value: impl Display) {
+        if !self.setting_bool(SETTING_DEBUG_OVERLAY) {
+            return;
+        }
+        let value = value.to_string();
+        let watches = &mut self.debug.watches;
+        match watches.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = value,
+            None => watches.push((name.to_string(), value)),
+        }
+    }
+
+    /// Adds the seconds spent in the update and drawing of this frame to the graph
+    pub(crate) fn record_timing(&mut self, update: f32, draw: f32) {
+        let timings = &mut self.debug.timings;
+        if timings.len() == HISTORY {
+            timings.pop_front();
+        }
+        timings.push_back(Timing {
+            frame: self.input.delta,
+            update,
+            draw,
+        });
+    }
+
+    /// Draws the frame time graph and the watched values in the top right corner
+    /// if [`SETTING_DEBUG_OVERLAY`] is on
+    pub(crate) fn draw_debug_overlay(&self) {
+        if !self.setting_bool(SETTING_DEBUG_OVERLAY) {
+            return;
+        }
+        let debug = &self.debug;
+        let th = TEXT_HEIGHT * screen_height() / 2.0;
+        let line = th * 1.2;
+        let width = HISTORY as f32 * BAR_WIDTH;
+        let lines = 2 + debug.watches.len();
+        let x = screen_width() - width - 10.0;
+        // below the FPS text
+        let y = th * 2.0;
+        draw_rectangle(
+            x - 5.0,
+            y - 5.0,
+            width + 10.0,
+            GRAPH_HEIGHT + line * lines as f32 + 10.0,
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        );
+
+        // bars from the bottom: update, drawing, then the rest of the frame
+        let bottom = y + GRAPH_HEIGHT;
+        let scale = GRAPH_HEIGHT / GRAPH_MAX;
+        for (i, timing) in debug.timings.iter().enumerate() {
+            let bar_x = x + i as f32 * BAR_WIDTH;
+            let frame = timing.frame.min(GRAPH_MAX) * scale;
+            let update = timing.update.min(GRAPH_MAX) * scale;
+            let draw = timing.draw.min(GRAPH_MAX - timing.update).max(0.0) * scale;
+            let color = budget_color(timing.frame);
+            draw_rectangle(bar_x, bottom - frame, BAR_WIDTH, frame, color);
+            draw_rectangle(bar_x, bottom - update, BAR_WIDTH, update, UPDATE_COLOR);
+            draw_rectangle(bar_x, bottom - update - draw, BAR_WIDTH, draw, DRAW_COLOR);
+        }
+        // 60 and 30 frames per second
+        for target in [1.0 / 60.0, 1.0 / 30.0] {
+            let target_y = bottom - target * scale;
+            draw_line(x, target_y, x + width, target_y, 1.0, GRAY);
+        }
+
+        let frame = debug.average(|t| t.frame);
+        let fps = if frame > 0.0 { 1.0 / frame } else { 0.0 };
+        let mut text_y = bottom + line;
+        let text = format!("frame {:.1} ms ({fps:.0} fps)", frame * 1000.0);
+        draw_text(&text, x, text_y, th, WHITE);
+        text_y += line;
+        let update = format!("update {:.2} ms", debug.average(|t| t.update) * 1000.0);
+        draw_text(&update, x, text_y, th, UPDATE_COLOR);
+        let draw = format!("draw {:.2} ms", debug.average(|t| t.draw) * 1000.0);
+        draw_text(&draw, x + width / 2.0, text_y, th, DRAW_COLOR);
+        for (name, value) in &debug.watches {
+            text_y += line;
+            draw_text(&format!("{name}: {value}"), x, text_y, th, WHITE);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use crate::{Context, GameState};
+
+/// A file picked by the user through [`Context::open_file`](crate::Context::open_file)
+#[derive(Debug, Clone)]
+pub struct OpenedFile {
+    /// Name of the file without any directories
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl OpenedFile {
+    /// Lowercase extension of the file name, if there is one
+    #[must_use]
+    pub fn extension(&self) -> Option<String> {
+        let (stem, ext) = self.name.rsplit_once('.')?;
+        if stem.is_empty() {
+            return None;
+        }
+        Some(ext.to_lowercase())
+    }
+
+    /// Content of the file as text, replacing invalid UTF-8
+    #[must_use]
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).to_string()
+    }
+}
+
+/// Options for the file picker of [`Context::open_file_with`] and [`Context::open_files`]
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    pub(crate) title: Option<String>,
+    pub(crate) filters: Vec<(String, Vec<String>)>,
+}
+
+impl OpenOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Title of the dialog. Browsers don't support this and ignore it.
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Only allow files with one of the extensions, given without the leading dot
+    #[must_use]
+    pub fn filter(mut self, name: &str, extensions: &[&str]) -> Self {
+        self.filters.push((
+            name.to_string(),
+            extensions.iter().map(ToString::to_string).collect(),
+        ));
+        self
+    }
+
+    /// Value for the `accept` attribute of a html file input
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn accept(&self) -> String {
+        self.filters
+            .iter()
+            .flat_map(|(_, extensions)| extensions)
+            .map(|ext| format!(".{ext}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Lets the user pick a file and reads it.
+    /// Returns `Ok(None)` if the dialog was cancelled.
+    pub async fn open_file(&self) -> Result<Option<OpenedFile>, FileError> {
+        self.open_file_with(&OpenOptions::default()).await
+    }
+}
+
+#[derive(Debug)]
+pub enum FileError {
+    /// Reading or writing the file on disk failed
+    Io(std::io::Error),
+    /// The browser reported an error
+    Browser(String),
+}
+
+impl Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileError::Io(e) => write!(f, "io error: {e}"),
+            FileError::Browser(e) => write!(f, "browser error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+impl From<std::io::Error> for FileError {
+    fn from(e: std::io::Error) -> Self {
+        FileError::Io(e)
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, gamepad::Snapshot, input::parse_key};
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+
+/// Everything a game can read about the input and time of one frame.
+///
+/// The game loop captures this from macroquad every frame, or reads it from a recording
+/// while replaying, so games have to ask the [`Context`] instead of macroquad to be replayable.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FrameInput {
+    pub time: f64,
+    pub delta: f32,
+    pub screen: Vec2,
+    pub keys_down: HashSet<KeyCode>,
+    pub keys_pressed: HashSet<KeyCode>,
+    pub mouse: Vec2,
+    pub wheel: f32,
+    /// Bits of [`MOUSE_BUTTONS`]
+    pub mouse_down: u8,
+    pub mouse_pressed: u8,
+    pub mouse_released: u8,
+    pub touches: Vec<Touch>,
+    pub gamepads: Vec<Snapshot>,
+    /// Characters typed in this frame, in order
+    pub chars: Vec<char>,
+}
+
+impl FrameInput {
+    pub(crate) fn capture() -> Self {
+        let mouse_bits = |check: fn(MouseButton) -> bool| {
+            MOUSE_BUTTONS
+                .iter()
+                .enumerate()
+                .filter(|(_, button)| check(**button))
+                .fold(0, |bits, (i, _)| bits | 1 << i)
+        };
+        Self {
+            time: get_time(),
+            delta: get_frame_time(),
+            screen: vec2(screen_width(), screen_height()),
+            keys_down: get_keys_down(),
+            keys_pressed: get_keys_pressed(),
+            mouse: mouse_position().into(),
+            wheel: mouse_wheel().1,
+            mouse_down: mouse_bits(is_mouse_button_down),
+            mouse_pressed: mouse_bits(is_mouse_button_pressed),
+            mouse_released: mouse_bits(is_mouse_button_released),
+            touches: touches(),
+            gamepads: crate::gamepad::poll(),
+            chars: typed_chars(),
+        }
+    }
+
+    /// Writes the frame as a single line of space separated fields
+    pub(crate) fn to_line(&self) -> String {
+        let keys = |keys: &HashSet<KeyCode>| {
+            let mut names: Vec<String> = keys.iter().map(|key| format!("{key:?}")).collect();
+            names.sort();
+            list(names)
+        };
+        let touches = list(self.touches.iter().map(|touch| {
+            let phase = match touch.phase {
+                TouchPhase::Started => 's',
+                TouchPhase::Stationary => 'h',
+                TouchPhase::Moved => 'm',
+                TouchPhase::Ended => 'e',
+                TouchPhase::Cancelled => 'c',
+            };
+            format!(
+                "{}:{phase}:{}:{}",
+                touch.id, touch.position.x, touch.position.y
+            )
+        }));
+        let gamepads = list(self.gamepads.iter().copied().map(Snapshot::to_field));
+        // as numbers, so spaces and commas don't break the line
+        let chars = list(self.chars.iter().map(|c| u32::from(*c).to_string()));
+        format!(
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            self.time,
+            self.delta,
+            self.screen.x,
+            self.screen.y,
+            keys(&self.keys_down),
+            keys(&self.keys_pressed),
+            self.mouse.x,
+            self.mouse.y,
+            self.wheel,
+            self.mouse_down,
+            self.mouse_pressed,
+            self.mouse_released,
+            touches,
+            gamepads,
+            chars,
+        )
+    }
+
+    pub(crate) fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split(' ');
+        let mut next = || fields.next();
+        let keys =
+            |field: &str| -> HashSet<KeyCode> { items(field).filter_map(parse_key).collect() };
+        Some(Self {
+            time: next()?.parse().ok()?,
+            delta: next()?.parse().ok()?,
+            screen: vec2(next()?.parse().ok()?, next()?.parse().ok()?),
+            keys_down: keys(next()?),
+            keys_pressed: keys(next()?),
+            mouse: vec2(next()?.parse().ok()?, next()?.parse().ok()?),
+            wheel: next()?.parse().ok()?,
+            mouse_down: next()?.parse().ok()?,
+            mouse_pressed: next()?.parse().ok()?,
+            mouse_released: next()?.parse().ok()?,
+            touches: items(next()?).map(parse_touch).collect::<Option<_>>()?,
+            gamepads: items(next()?)
+                .map(Snapshot::from_field)
+                .collect::<Option<_>>()?,
+            chars: items(next()?)
+                .map(|c| char::from_u32(c.parse().ok()?))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Adds the presses of `other` to this frame
+    pub(crate) fn add_pressed(&mut self, other: &FrameInput) {
+        self.keys_pressed.extend(&other.keys_pressed);
+        self.mouse_pressed |= other.mouse_pressed;
+        self.mouse_released |= other.mouse_released;
+        self.wheel += other.wheel;
+        self.chars.extend(&other.chars);
+    }
+
+    /// Forgets the presses of this frame, keeping what is held down
+    pub(crate) fn clear_pressed(&mut self) {
+        self.keys_pressed.clear();
+        self.mouse_pressed = 0;
+        self.mouse_released = 0;
+        self.wheel = 0.0;
+        self.chars.clear();
+    }
+
+    pub(crate) fn mouse_bit(button: MouseButton) -> u8 {
+        MOUSE_BUTTONS
+            .iter()
+            .position(|b| *b == button)
+            .map_or(0, |i| 1 << i)
+    }
+}
+
+/// Empties macroquad's queue of typed characters, which has the last one on top
+fn typed_chars() -> Vec<char> {
+    let mut chars: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+    chars.reverse();
+    chars
+}
+
+/// Comma separated list, `-` if empty so the line can still be split at spaces
+fn list(items: impl IntoIterator<Item = String>) -> String {
+    let items: Vec<String> = items.into_iter().collect();
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join(",")
+    }
+}
+
+fn items(field: &str) -> impl Iterator<Item = &str> {
+    field
+        .split(',')
+        .filter(|item| !item.is_empty() && *item != "-")
+}
+
+fn parse_touch(field: &str) -> Option<Touch> {
+    let mut parts = field.split(':');
+    let id = parts.next()?.parse().ok()?;
+    let phase = match parts.next()? {
+        "s" => TouchPhase::Started,
+        "h" => TouchPhase::Stationary,
+        "m" => TouchPhase::Moved,
+        "e" => TouchPhase::Ended,
+        "c" => TouchPhase::Cancelled,
+        _ => return None,
+    };
+    let position = vec2(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?);
+    Some(Touch {
+        id,
+        phase,
+        position,
+    })
+}
+
+impl<S: GameState> Context<S> {
+    /// Makes `input` the input of the current frame and updates the state derived from it
+    pub(crate) fn begin_frame(&mut self, input: FrameInput) {
+        self.gamepads.update(input.gamepads.clone());
+        self.touches
+            .update(&input.touches, input.time, input.screen);
+        self.input = input;
+    }
+
+    /// Time in seconds since the game started, replayed from recordings
+    #[must_use]
+    pub fn time(&self) -> f64 {
+        self.input.time
+    }
+
+    /// Seconds since the last frame, the same value [`GameState::update`] gets
+    #[must_use]
+    pub fn frame_time(&self) -> f32 {
+        self.input.delta
+    }
+
+    #[must_use]
+    pub fn screen_width(&self) -> f32 {
+        self.input.screen.x
+    }
+
+    #[must_use]
+    pub fn screen_height(&self) -> f32 {
+        self.input.screen.y
+    }
+
+    #[must_use]
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.input.keys_down.contains(&key)
+    }
+
+    /// Returns true in the frame the key was pressed
+    #[must_use]
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.input.keys_pressed.contains(&key)
+    }
+
+    /// Any key pressed in this frame
+    #[must_use]
+    pub fn last_key_pressed(&self) -> Option<KeyCode> {
+        self.input.keys_pressed.iter().next().copied()
+    }
+
+    /// Characters typed in this frame, in order, for text input
+    #[must_use]
+    pub fn chars_typed(&self) -> &[char] {
+        &self.input.chars
+    }
+
+    #[must_use]
+    pub fn mouse_position(&self) -> Vec2 {
+        self.input.mouse
+    }
+
+    /// Vertical scroll of the mouse wheel in this frame
+    #[must_use]
+    pub fn mouse_wheel(&self) -> f32 {
+        self.input.wheel
+    }
+
+    #[must_use]
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.input.mouse_down & FrameInput::mouse_bit(button) != 0
+    }
+
+    #[must_use]
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.input.mouse_pressed & FrameInput::mouse_bit(button) != 0
+    }
+
+    #[must_use]
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.input.mouse_released & FrameInput::mouse_bit(button) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_input_survives_a_line_round_trip() {
+        let input = FrameInput {
+            time: 1.5,
+            delta: 0.016,
+            screen: vec2(800.0, 600.0),
+            keys_down: HashSet::from([KeyCode::A, KeyCode::Space]),
+            keys_pressed: HashSet::from([KeyCode::Space]),
+            mouse: vec2(10.5, 20.0),
+            wheel: -1.0,
+            mouse_down: 1,
+            mouse_pressed: 1,
+            mouse_released: 4,
+            touches: vec![Touch {
+                id: 3,
+                phase: TouchPhase::Moved,
+                position: vec2(5.0, 6.0),
+            }],
+            gamepads: vec![Snapshot::from_field("0:5:0.5/0/0/0/0/1").unwrap()],
+            // a space and a comma would break the line if written as they are
+            chars: vec!['a', ' ', ','],
+        };
+        let line = input.to_line();
+        let parsed = FrameInput::from_line(&line).unwrap();
+        assert_eq!(parsed.to_line(), line);
+        assert_eq!(parsed.time, 1.5);
+        assert_eq!(parsed.keys_down, input.keys_down);
+        assert_eq!(parsed.keys_pressed, input.keys_pressed);
+        assert_eq!(parsed.mouse, input.mouse);
+        assert_eq!(parsed.touches[0].phase, TouchPhase::Moved);
+        assert_eq!(parsed.chars, input.chars);
+    }
+
+    #[test]
+    fn empty_frame_input_survives_a_line_round_trip() {
+        let line = FrameInput::default().to_line();
+        let parsed = FrameInput::from_line(&line).unwrap();
+        assert!(parsed.keys_down.is_empty());
+        assert!(parsed.touches.is_empty());
+        assert!(parsed.chars.is_empty());
+    }
+
+    #[test]
+    fn frame_line_without_chars_is_rejected() {
+        let line = FrameInput::default().to_line();
+        let (without_chars, _) = line.rsplit_once(' ').unwrap();
+        assert!(FrameInput::from_line(without_chars).is_none());
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+#[cfg(all(target_arch = "wasm32", feature = "gamepad"))]
+use sapp_jsutils::JsObject;
+
+use crate::{Context, GameState};
+
+/// Buttons of a gamepad, named after their position in the standard layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    /// All buttons in the order of the browser standard gamepad mapping
+    const ALL: [GamepadButton; 16] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::West,
+        GamepadButton::North,
+        GamepadButton::LeftShoulder,
+        GamepadButton::RightShoulder,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+
+    /// Parses the debug name of a button
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|button| format!("{button:?}") == name)
+            .copied()
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Analog inputs of a gamepad.
+/// Sticks go from -1 to 1 with y pointing down, triggers from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadEvent {
+    /// A gamepad was plugged in and got the given id
+    Connected(usize),
+    Disconnected(usize),
+}
+
+/// State of one gamepad in a single frame
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Snapshot {
+    id: usize,
+    buttons: u32,
+    axes: [f32; 6],
+}
+
+impl Snapshot {
+    /// Field for recordings, `id:buttons:axis/axis/...`
+    pub(crate) fn to_field(self) -> String {
+        let axes: Vec<String> = self.axes.iter().map(ToString::to_string).collect();
+        format!("{}:{}:{}", self.id, self.buttons, axes.join("/"))
+    }
+
+    pub(crate) fn from_field(field: &str) -> Option<Self> {
+        let mut parts = field.split(':');
+        let id = parts.next()?.parse().ok()?;
+        let buttons = parts.next()?.parse().ok()?;
+        let mut axes = [0.0; 6];
+        for (axis, value) in axes.iter_mut().zip(parts.next()?.split('/')) {
+            *axis = value.parse().ok()?;
+        }
+        Some(Self { id, buttons, axes })
+    }
+}
+
+/// Reads the connected gamepads from the platform
+pub(crate) fn poll() -> Vec<Snapshot> {
+    backend::poll()
+}
+
+pub(crate) struct Gamepads {
+    current: Vec<Snapshot>,
+    previous: Vec<Snapshot>,
+    /// Buttons pressed this frame by gamepad id, kept apart so fixed updates can carry them
+    pressed: HashMap<usize, u32>,
+    events: Vec<GamepadEvent>,
+    next_repeat: HashMap<(usize, GamepadButton), f64>,
+    dead_zone: f32,
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self {
+            current: Vec::new(),
+            previous: Vec::new(),
+            pressed: HashMap::new(),
+            events: Vec::new(),
+            next_repeat: HashMap::new(),
+            dead_zone: 0.2,
+        }
+    }
+}
+
+impl Gamepads {
+    /// Sets the gamepads of this frame, called once per frame by the game loop
+    pub(crate) fn update(&mut self, snapshots: Vec<Snapshot>) {
+        self.previous = std::mem::replace(&mut self.current, snapshots);
+        self.current.sort_by_key(|pad| pad.id);
+        self.pressed.clear();
+        for pad in &self.current {
+            let was_down = self
+                .previous
+                .iter()
+                .find(|old| old.id == pad.id)
+                .map_or(0, |old| old.buttons);
+            let pressed = pad.buttons & !was_down;
+            if pressed != 0 {
+                self.pressed.insert(pad.id, pressed);
+            }
+        }
+        self.events.clear();
+        for pad in &self.current {
+            if !self.previous.iter().any(|old| old.id == pad.id) {
+                info!("Gamepad {} connected", pad.id);
+                self.events.push(GamepadEvent::Connected(pad.id));
+            }
+        }
+        for pad in &self.previous {
+            if !self.current.iter().any(|new| new.id == pad.id) {
+                info!("Gamepad {} disconnected", pad.id);
+                self.events.push(GamepadEvent::Disconnected(pad.id));
+            }
+        }
+    }
+
+    /// Forgets held buttons, so repeats start over
+    pub(crate) fn clear_repeat(&mut self) {
+        self.next_repeat.clear();
+    }
+
+    fn get(&self, id: usize) -> Option<&Snapshot> {
+        self.current.iter().find(|pad| pad.id == id)
+    }
+
+    /// Buttons pressed this frame by gamepad id
+    pub(crate) fn pressed(&self) -> &HashMap<usize, u32> {
+        &self.pressed
+    }
+
+    /// Adds presses of another frame to this one
+    pub(crate) fn add_pressed(&mut self, pressed: &HashMap<usize, u32>) {
+        for (id, buttons) in pressed {
+            *self.pressed.entry(*id).or_default() |= buttons;
+        }
+    }
+
+    /// Forgets the presses of this frame, keeping what is held down
+    pub(crate) fn clear_pressed(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Ids of all connected gamepads, sorted
+    #[must_use]
+    pub fn gamepads(&self) -> Vec<usize> {
+        self.gamepads.current.iter().map(|pad| pad.id).collect()
+    }
+
+    /// Id of the n-th connected gamepad, e.g. to give each player their own
+    #[must_use]
+    pub fn nth_gamepad(&self, n: usize) -> Option<usize> {
+        self.gamepads.current.get(n).map(|pad| pad.id)
+    }
+
+    /// Gamepads that were connected or disconnected this frame
+    #[must_use]
+    pub fn gamepad_events(&self) -> &[GamepadEvent] {
+        &self.gamepads.events
+    }
+
+    /// Axis values below the dead zone are reported as 0, defaults to 0.2
+    pub fn set_gamepad_dead_zone(&mut self, dead_zone: f32) {
+        self.gamepads.dead_zone = dead_zone;
+    }
+
+    #[must_use]
+    pub fn is_gamepad_button_down(&self, id: usize, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(id)
+            .is_some_and(|pad| pad.buttons & button.bit() != 0)
+    }
+
+    /// Returns true in the frame the button was pressed
+    #[must_use]
+    pub fn is_gamepad_button_pressed(&self, id: usize, button: GamepadButton) -> bool {
+        self.gamepads
+            .pressed
+            .get(&id)
+            .is_some_and(|buttons| buttons & button.bit() != 0)
+    }
+
+    /// Like [`Context::is_key_pressed_loop`] for a gamepad button
+    pub fn is_gamepad_button_pressed_loop(&mut self, id: usize, button: GamepadButton) -> bool {
+        let down = self.is_gamepad_button_down(id, button);
+        let next = self
+            .gamepads
+            .next_repeat
+            .entry((id, button))
+            .or_insert(f64::NAN);
+        self.default_repeat.update(next, down, self.input.time)
+    }
+
+    /// Value of the axis with the dead zone applied
+    #[must_use]
+    pub fn gamepad_axis(&self, id: usize, axis: GamepadAxis) -> f32 {
+        let Some(pad) = self.gamepads.get(id) else {
+            return 0.0;
+        };
+        let dead_zone = self.gamepads.dead_zone;
+        match axis {
+            GamepadAxis::LeftX | GamepadAxis::LeftY => {
+                let stick = vec2(pad.axes[0], pad.axes[1]);
+                let stick = apply_dead_zone(stick, dead_zone);
+                if axis == GamepadAxis::LeftX {
+                    stick.x
+                } else {
+                    stick.y
+                }
+            }
+            GamepadAxis::RightX | GamepadAxis::RightY => {
+                let stick = vec2(pad.axes[2], pad.axes[3]);
+                let stick = apply_dead_zone(stick, dead_zone);
+                if axis == GamepadAxis::RightX {
+                    stick.x
+                } else {
+                    stick.y
+                }
+            }
+            GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => {
+                let value = pad.axes[axis as usize];
+                if value < dead_zone {
+                    0.0
+                } else {
+                    (value - dead_zone) / (1.0 - dead_zone)
+                }
+            }
+        }
+    }
+}
+
+/// Radial dead zone, rescaled so the output still starts at 0
+fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+    if length < dead_zone {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    stick / length * scaled
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "gamepad"))]
+mod backend {
+    use std::cell::RefCell;
+
+    use gilrs::{Axis, Button, Gilrs};
+    use macroquad::logging::warn;
+
+    use super::{GamepadButton, Snapshot};
+
+    thread_local! {
+        // gilrs is not Send, so it can't live in the Context
+        static GILRS: RefCell<Option<Gilrs>> = RefCell::new(Gilrs::new().inspect_err(|e| {
+            warn!("Gamepads are not available: {}", e);
+        }).ok());
+    }
+
+    pub(super) fn poll() -> Vec<Snapshot> {
+        GILRS.with_borrow_mut(|gilrs| {
+            let Some(gilrs) = gilrs else {
+                return Vec::new();
+            };
+            // events have to be consumed to update the gamepad state
+            while gilrs.next_event().is_some() {}
+            gilrs
+                .gamepads()
+                .map(|(id, pad)| {
+                    let mut buttons = 0;
+                    for button in GamepadButton::ALL {
+                        if pad.is_pressed(to_gilrs(button)) {
+                            buttons |= button.bit();
+                        }
+                    }
+                    let trigger = |button| {
+                        pad.button_data(button)
+                            .map_or(0.0, gilrs::ev::state::ButtonData::value)
+                    };
+                    Snapshot {
+                        id: id.into(),
+                        buttons,
+                        axes: [
+                            pad.value(Axis::LeftStickX),
+                            -pad.value(Axis::LeftStickY),
+                            pad.value(Axis::RightStickX),
+                            -pad.value(Axis::RightStickY),
+                            trigger(Button::LeftTrigger2),
+                            trigger(Button::RightTrigger2),
+                        ],
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_gilrs(button: GamepadButton) -> Button {
+        match button {
+            GamepadButton::South => Button::South,
+            GamepadButton::East => Button::East,
+            GamepadButton::West => Button::West,
+            GamepadButton::North => Button::North,
+            GamepadButton::LeftShoulder => Button::LeftTrigger,
+            GamepadButton::RightShoulder => Button::RightTrigger,
+            GamepadButton::LeftTrigger => Button::LeftTrigger2,
+            GamepadButton::RightTrigger => Button::RightTrigger2,
+            GamepadButton::Select => Button::Select,
+            GamepadButton::Start => Button::Start,
+            GamepadButton::LeftStick => Button::LeftThumb,
+            GamepadButton::RightStick => Button::RightThumb,
+            GamepadButton::DPadUp => Button::DPadUp,
+            GamepadButton::DPadDown => Button::DPadDown,
+            GamepadButton::DPadLeft => Button::DPadLeft,
+            GamepadButton::DPadRight => Button::DPadRight,
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "gamepad"))]
+mod backend {
+    use super::{GamepadButton, JsObject, Snapshot};
+
+    unsafe extern "C" {
+        fn gamepads_js() -> JsObject;
+    }
+
+    /// Reads `navigator.getGamepads()` through `functions.js`, which returns
+    /// `[{ index, buttons: [0 | 1], axes: [number] }]` in the standard mapping
+    pub(super) fn poll() -> Vec<Snapshot> {
+        let pads = unsafe { gamepads_js() };
+        let count = pads.field_u32("length");
+        (0..count)
+            .map(|i| {
+                let pad = pads.field(&i.to_string());
+                let buttons_obj = pad.field("buttons");
+                let mut buttons = 0;
+                for (index, button) in GamepadButton::ALL.into_iter().enumerate() {
+                    if buttons_obj.field_u32(&index.to_string()) != 0 {
+                        buttons |= button.bit();
+                    }
+                }
+                let axes_obj = pad.field("axes");
+                let mut axes = [0.0; 6];
+                for (index, axis) in axes.iter_mut().enumerate() {
+                    *axis = axes_obj.field_f32(&index.to_string());
+                }
+                Snapshot {
+                    id: pad.field_u32("index") as usize,
+                    buttons,
+                    axes,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Without the `gamepad` feature no gamepad is ever connected
+#[cfg(not(feature = "gamepad"))]
+mod backend {
+    use super::Snapshot;
+
+    pub(super) fn poll() -> Vec<Snapshot> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, frame::FrameInput, storage::Storage};
+
+/// Frame time used by [`Harness::step`], 60 frames per second
+pub const DEFAULT_FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Drives a [`Context`] without a window, for tests of [`GameState`] implementations.
+///
+/// Input is scripted by pressing and releasing keys, buttons and touches before each step.
+/// The clock only advances by the delta given to [`Harness::step_by`] and the screen has
+/// a fixed size, so runs are the same on every machine.
+/// The [`Storage`] of the game starts empty and is never written.
+///
+/// ```ignore
+/// let mut harness = Harness::<State>::new();
+/// harness.press_key(KeyCode::Space);
+/// harness.step();
+/// harness.release_key(KeyCode::Space);
+/// harness.run(60);
+/// assert!(harness.state().drawing_mode);
+/// ```
+///
+/// Random numbers come from macroquad's global generator, which is seeded when the
+/// harness is created. Tests running in parallel share it, so tests that depend
+/// on random values should set the state they need instead.
+pub struct Harness<S: GameState> {
+    ctx: Context<S>,
+    /// Input of the next step, presses are cleared after each step
+    input: FrameInput,
+    frame: u64,
+}
+
+impl<S: GameState> Default for Harness<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: GameState> Harness<S> {
+    /// Creates the state with seed 0 on an 800x600 screen and calls [`GameState::init`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        let mut ctx = Context::<S> {
+            storage: Some(Storage::in_memory(S::storage_name(), BTreeMap::new())),
+            ..Default::default()
+        };
+        let input = FrameInput {
+            screen: vec2(800.0, 600.0),
+            ..Default::default()
+        };
+        ctx.input = input.clone();
+        ctx.restart(seed);
+        Self {
+            ctx,
+            input,
+            frame: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn ctx(&self) -> &Context<S> {
+        &self.ctx
+    }
+
+    pub fn ctx_mut(&mut self) -> &mut Context<S> {
+        &mut self.ctx
+    }
+
+    #[must_use]
+    pub fn state(&self) -> &S {
+        &self.ctx.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.ctx.state
+    }
+
+    /// Number of steps so far
+    #[must_use]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Time of the fake clock in seconds
+    #[must_use]
+    pub fn time(&self) -> f64 {
+        self.input.time
+    }
+
+    pub fn set_screen_size(&mut self, width: f32, height: f32) {
+        self.input.screen = vec2(width, height);
+    }
+
+    /// The key is pressed in the next step and held until [`Harness::release_key`]
+    pub fn press_key(&mut self, key: KeyCode) {
+        if self.input.keys_down.insert(key) {
+            self.input.keys_pressed.insert(key);
+        }
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) {
+        self.input.keys_down.remove(&key);
+    }
+
+    /// Presses the key for a single step
+    pub fn tap_key(&mut self, key: KeyCode) {
+        self.press_key(key);
+        self.step();
+        self.release_key(key);
+    }
+
+    /// Types `text` in the next step, for games that read [`Context::chars_typed`]
+    pub fn type_text(&mut self, text: &str) {
+        self.input.chars.extend(text.chars());
+    }
+
+    pub fn set_mouse_position(&mut self, position: Vec2) {
+        self.input.mouse = position;
+    }
+
+    pub fn press_mouse_button(&mut self, button: MouseButton) {
+        let bit = FrameInput::mouse_bit(button);
+        if self.input.mouse_down & bit == 0 {
+            self.input.mouse_down |= bit;
+            self.input.mouse_pressed |= bit;
+        }
+    }
+
+    pub fn release_mouse_button(&mut self, button: MouseButton) {
+        let bit = FrameInput::mouse_bit(button);
+        if self.input.mouse_down & bit != 0 {
+            self.input.mouse_down &= !bit;
+            self.input.mouse_released |= bit;
+        }
+    }
+
+    /// Moves the mouse to `position` and clicks the left button for a single step
+    pub fn click(&mut self, position: Vec2) {
+        self.set_mouse_position(position);
+        self.press_mouse_button(MouseButton::Left);
+        self.step();
+        self.release_mouse_button(MouseButton::Left);
+    }
+
+    pub fn scroll(&mut self, amount: f32) {
+        self.input.wheel += amount;
+    }
+
+    /// Adds a touch to the next step. Touches have to be sent every step they are active,
+    /// with [`TouchPhase::Started`] first and [`TouchPhase::Ended`] last.
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) {
+        self.input.touches.push(Touch {
+            id,
+            phase,
+            position,
+        });
+    }
+
+    /// Runs one frame of [`DEFAULT_FRAME_TIME`]
+    pub fn step(&mut self) {
+        self.step_by(DEFAULT_FRAME_TIME);
+    }
+
+    /// Advances the clock by `delta` seconds and runs one frame with the scripted input,
+    /// updating the state or the top [`Scene`](crate::Scene) like the game loop does
+    pub fn step_by(&mut self, delta: f32) {
+        self.input.delta = delta;
+        self.input.time += f64::from(delta);
+        self.ctx.begin_frame(self.input.clone());
+        pollster::block_on(self.ctx.update_frame());
+        self.input.clear_pressed();
+        self.input.touches.clear();
+        self.frame += 1;
+    }
+
+    /// Runs `frames` frames of [`DEFAULT_FRAME_TIME`] without changing the input
+    pub fn run(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Steps until `condition` is true for the state, at most `max_frames` times.
+    /// Returns whether the condition was met.
+    pub fn run_until(&mut self, max_frames: u32, condition: impl Fn(&S) -> bool) -> bool {
+        for _ in 0..max_frames {
+            if condition(self.state()) {
+                return true;
+            }
+            self.step();
+        }
+        condition(self.state())
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, GamepadButton};
+
+/// How a held key repeats, independent of the frame rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyRepeat {
+    /// Seconds between the first press and the first repeat
+    pub delay: f64,
+    /// Seconds between two repeats
+    pub interval: f64,
+}
+
+impl KeyRepeat {
+    pub const DEFAULT: Self = Self {
+        delay: 0.5,
+        interval: 0.05,
+    };
+
+    #[must_use]
+    pub fn new(delay: f64, interval: f64) -> Self {
+        Self { delay, interval }
+    }
+
+    /// Returns true on the first press and on every repeat.
+    /// `next` is the time of the next repeat and NAN while the key is up.
+    pub(crate) fn update(self, next: &mut f64, down: bool, now: f64) -> bool {
+        if !down {
+            *next = f64::NAN;
+            return false;
+        }
+        if next.is_nan() {
+            *next = now + self.delay;
+            return true;
+        }
+        if *next <= now {
+            // don't pile up repeats after a long frame
+            *next = (*next + self.interval).max(now);
+            return true;
+        }
+        false
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Keys and gamepad buttons of all actions, kept with recordings
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bindings {
+    pub(crate) keys: BTreeMap<String, Vec<KeyCode>>,
+    pub(crate) buttons: BTreeMap<String, (Option<usize>, Vec<GamepadButton>)>,
+}
+
+/// Keys bound to named actions
+#[derive(Default)]
+pub(crate) struct Actions {
+    bindings: BTreeMap<String, Vec<KeyCode>>,
+    /// Gamepad buttons of an action, on the n-th gamepad or on any gamepad if `None`
+    buttons: BTreeMap<String, (Option<usize>, Vec<GamepadButton>)>,
+    repeat: BTreeMap<String, KeyRepeat>,
+    next_repeat: BTreeMap<String, f64>,
+    rebinding: Option<String>,
+}
+
+impl<S: GameState> Context<S> {
+    /// Declares an action with its default keys.
+    /// Keys the player bound to the action in an earlier run take precedence.
+    pub fn define_action(&mut self, action: &str, default_keys: &[KeyCode]) {
+        let saved: Option<String> = self.storage().get(&storage_key(action));
+        let keys = saved.map_or_else(|| default_keys.to_vec(), |saved| parse_keys(&saved));
+        self.actions.bindings.insert(action.to_string(), keys);
+    }
+
+    /// Binds the action to the given keys and remembers them for the next run
+    pub fn bind_action(&mut self, action: &str, keys: &[KeyCode]) {
+        self.actions
+            .bindings
+            .insert(action.to_string(), keys.to_vec());
+        let names = keys
+            .iter()
+            .map(|key| format!("{key:?}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.storage().set(&storage_key(action), &names);
+    }
+
+    /// Binds gamepad buttons to the action in addition to its keys.
+    /// With a `gamepad` of `Some(n)` only the n-th connected gamepad triggers the action.
+    pub fn bind_action_buttons(
+        &mut self,
+        action: &str,
+        gamepad: Option<usize>,
+        buttons: &[GamepadButton],
+    ) {
+        self.actions
+            .buttons
+            .insert(action.to_string(), (gamepad, buttons.to_vec()));
+    }
+
+    /// Ids of the gamepads and the buttons that trigger the action
+    fn action_buttons(&self, action: &str) -> Vec<(usize, GamepadButton)> {
+        let Some((slot, buttons)) = self.actions.buttons.get(action) else {
+            return Vec::new();
+        };
+        let pads = match slot {
+            Some(n) => self.nth_gamepad(*n).into_iter().collect(),
+            None => self.gamepads(),
+        };
+        pads.into_iter()
+            .flat_map(|pad| buttons.iter().map(move |button| (pad, *button)))
+            .collect()
+    }
+
+    /// Keys and gamepad buttons of all actions
+    pub(crate) fn bindings(&self) -> Bindings {
+        Bindings {
+            keys: self.actions.bindings.clone(),
+            buttons: self.actions.buttons.clone(),
+        }
+    }
+
+    /// Replaces the keys and buttons of the given actions without remembering them for the next run
+    pub(crate) fn set_bindings(&mut self, bindings: Bindings) {
+        self.actions.bindings.extend(bindings.keys);
+        self.actions.buttons.extend(bindings.buttons);
+    }
+
+    /// The next key that is pressed will be bound to the action
+    pub fn rebind_action(&mut self, action: &str) {
+        self.actions.rebinding = Some(action.to_string());
+    }
+
+    /// The action waiting for a key from [`Context::rebind_action`]
+    #[must_use]
+    pub fn rebinding_action(&self) -> Option<&str> {
+        self.actions.rebinding.as_deref()
+    }
+
+    /// Binds the pressed key if an action is waiting for one.
+    /// Returns true if the input of this frame was used for rebinding.
+    pub(crate) fn update_rebinding(&mut self) -> bool {
+        let Some(action) = self.actions.rebinding.clone() else {
+            return false;
+        };
+        if let Some(key) = self.last_key_pressed() {
+            self.bind_action(&action, &[key]);
+            self.actions.rebinding = None;
+        }
+        true
+    }
+
+    /// All defined actions with their keys
+    pub fn actions(&self) -> impl Iterator<Item = (&str, &[KeyCode])> {
+        self.actions
+            .bindings
+            .iter()
+            .map(|(action, keys)| (action.as_str(), keys.as_slice()))
+    }
+
+    #[must_use]
+    pub fn action_keys(&self, action: &str) -> &[KeyCode] {
+        self.actions.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true while any key, gamepad button or virtual button of the action is held down
+    #[must_use]
+    pub fn is_action_down(&self, action: &str) -> bool {
+        self.action_keys(action)
+            .iter()
+            .any(|key| self.is_key_down(*key))
+            || self.is_virtual_button_down(action)
+            || self
+                .action_buttons(action)
+                .into_iter()
+                .any(|(pad, button)| self.is_gamepad_button_down(pad, button))
+    }
+
+    /// Returns true in the frame any key, gamepad button or virtual button of the action was pressed
+    #[must_use]
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.action_keys(action)
+            .iter()
+            .any(|key| self.is_key_pressed(*key))
+            || self.is_virtual_button_pressed(action)
+            || self
+                .action_buttons(action)
+                .into_iter()
+                .any(|(pad, button)| self.is_gamepad_button_pressed(pad, button))
+    }
+
+    /// Like [`Context::is_key_pressed_loop`] for any key of the action
+    pub fn is_action_pressed_loop(&mut self, action: &str) -> bool {
+        let down = self.is_action_down(action);
+        let repeat = self
+            .actions
+            .repeat
+            .get(action)
+            .copied()
+            .unwrap_or(self.default_repeat);
+        let next = self
+            .actions
+            .next_repeat
+            .entry(action.to_string())
+            .or_insert(f64::NAN);
+        repeat.update(next, down, self.input.time)
+    }
+
+    /// Sets how the action repeats in [`Context::is_action_pressed_loop`]
+    pub fn set_action_repeat(&mut self, action: &str, repeat: KeyRepeat) {
+        self.actions.repeat.insert(action.to_string(), repeat);
+    }
+}
+
+fn storage_key(action: &str) -> String {
+    format!("keys.{action}")
+}
+
+fn parse_keys(names: &str) -> Vec<KeyCode> {
+    names.split(',').filter_map(parse_key).collect()
+}
+
+/// Parses the debug name of a key
+pub(crate) fn parse_key(name: &str) -> Option<KeyCode> {
+    KEYS.iter().find(|key| format!("{key:?}") == name).copied()
+}
+
+/// Display name of a key
+#[must_use]
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key").unwrap_or(&name).to_string()
+}
+
+const KEYS: &[KeyCode] = &[
+    KeyCode::Space,
+    KeyCode::Apostrophe,
+    KeyCode::Comma,
+    KeyCode::Minus,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Semicolon,
+    KeyCode::Equal,
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::LeftBracket,
+    KeyCode::Backslash,
+    KeyCode::RightBracket,
+    KeyCode::GraveAccent,
+    KeyCode::World1,
+    KeyCode::World2,
+    KeyCode::Escape,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Right,
+    KeyCode::Left,
+    KeyCode::Down,
+    KeyCode::Up,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::CapsLock,
+    KeyCode::ScrollLock,
+    KeyCode::NumLock,
+    KeyCode::PrintScreen,
+    KeyCode::Pause,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::F25,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpDecimal,
+    KeyCode::KpDivide,
+    KeyCode::KpMultiply,
+    KeyCode::KpSubtract,
+    KeyCode::KpAdd,
+    KeyCode::KpEnter,
+    KeyCode::KpEqual,
+    KeyCode::LeftShift,
+    KeyCode::LeftControl,
+    KeyCode::LeftAlt,
+    KeyCode::LeftSuper,
+    KeyCode::RightShift,
+    KeyCode::RightControl,
+    KeyCode::RightAlt,
+    KeyCode::RightSuper,
+    KeyCode::Menu,
+    KeyCode::Back,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_repeat_fires_on_press_after_the_delay_and_every_interval() {
+        let repeat = KeyRepeat::new(0.5, 0.1);
+        let mut next = f64::NAN;
+        assert!(repeat.update(&mut next, true, 0.0));
+        assert!(!repeat.update(&mut next, true, 0.3));
+        assert!(repeat.update(&mut next, true, 0.5));
+        assert!(!repeat.update(&mut next, true, 0.55));
+        assert!(repeat.update(&mut next, true, 0.6));
+        assert!(!repeat.update(&mut next, false, 0.7));
+        assert!(next.is_nan());
+        assert!(repeat.update(&mut next, true, 0.8));
+    }
+
+    #[test]
+    fn key_repeat_does_not_pile_up_after_a_long_frame() {
+        let repeat = KeyRepeat::new(0.5, 0.1);
+        let mut next = f64::NAN;
+        repeat.update(&mut next, true, 0.0);
+        // 25 repeats are overdue after 3 seconds, frames of 10ms afterwards only get
+        // the usual ones instead of one each until they caught up
+        let repeats = (0..30)
+            .filter(|i| repeat.update(&mut next, true, 3.0 + f64::from(*i) * 0.01))
+            .count();
+        assert!(repeats <= 5, "{repeats} repeats");
+    }
+}
@@ -0,0 +1,114 @@
+//! Request/response bridge between rust and the js plugin in `site/functions.js`.
+//!
+//! Every call into js gets a request id. Js answers by calling `js_response` with that id and a
+//! tagged payload object `{ kind, value, name? }`, so several requests can be outstanding at once.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+        mpsc::{Receiver, Sender},
+    },
+};
+
+use macroquad::prelude::*;
+use sapp_jsutils::JsObject;
+
+use crate::{Context, GameState};
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+static PENDING: Mutex<BTreeMap<u32, Sender<JsPayload>>> = Mutex::new(BTreeMap::new());
+
+/// Value sent back from js
+#[derive(Debug, Clone)]
+pub enum JsPayload {
+    /// The request finished without a value, e.g. a cancelled dialog
+    Empty,
+    String(String),
+    Bytes {
+        name: Option<String>,
+        data: Vec<u8>,
+    },
+    Number(f32),
+    Error(String),
+    List(Vec<JsPayload>),
+}
+
+impl JsPayload {
+    fn from_js(obj: &JsObject) -> Self {
+        let mut kind = String::new();
+        obj.field("kind").to_string(&mut kind);
+        match kind.as_str() {
+            "empty" => JsPayload::Empty,
+            "string" => {
+                let mut value = String::new();
+                obj.field("value").to_string(&mut value);
+                JsPayload::String(value)
+            }
+            "bytes" => {
+                let name = obj.have_field("name").then(|| {
+                    let mut name = String::new();
+                    obj.field("name").to_string(&mut name);
+                    name
+                });
+                let mut data = Vec::new();
+                obj.field("value").to_byte_buffer(&mut data);
+                JsPayload::Bytes { name, data }
+            }
+            "number" => JsPayload::Number(obj.field_f32("value")),
+            "error" => {
+                let mut value = String::new();
+                obj.field("value").to_string(&mut value);
+                JsPayload::Error(value)
+            }
+            "list" => {
+                let list = obj.field("value");
+                let len = list.field_u32("length");
+                let items = (0..len)
+                    .map(|i| JsPayload::from_js(&list.field(&i.to_string())))
+                    .collect();
+                JsPayload::List(items)
+            }
+            _ => JsPayload::Error(format!("unknown response kind: {kind}")),
+        }
+    }
+}
+
+/// Registers a new request and returns its id and the receiver for the response
+fn register() -> (u32, Receiver<JsPayload>) {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = std::sync::mpsc::channel();
+    PENDING.lock().unwrap().insert(id, tx);
+    (id, rx)
+}
+
+impl<S: GameState> Context<S> {
+    /// Calls into js with a fresh request id and keeps drawing until js responded.
+    /// The js side has to answer with `respond(id, payload)` from `functions.js`.
+    pub async fn js_request(&self, call: impl FnOnce(u32)) -> JsPayload {
+        let (id, rx) = register();
+        call(id);
+        loop {
+            match rx.try_recv() {
+                Ok(payload) => break payload,
+                Err(_) => {
+                    self.draw_state();
+                    next_frame().await;
+                }
+            }
+        }
+    }
+}
+
+/// Called from js to answer the request with the given id
+#[unsafe(no_mangle)]
+pub extern "C" fn js_response(id: u32, js_obj: JsObject) {
+    let Some(sender) = PENDING.lock().unwrap().remove(&id) else {
+        // This should never happen, but if it does, we just ignore the response
+        warn!("Received a response for unknown request {}", id);
+        return;
+    };
+    // the receiver may already be gone if the request was abandoned
+    let _ = sender.send(JsPayload::from_js(&js_obj));
+}
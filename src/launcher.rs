@@ -0,0 +1,122 @@
+use std::{future::Future, pin::Pin};
+
+use macroquad::prelude::*;
+
+use crate::{GameState, TEXT_HEIGHT, draw_text_centered};
+
+/// Metadata of a game, shown in the [`Launcher`] menu
+pub trait GameInfo: GameState + 'static {
+    fn name() -> &'static str;
+    fn description() -> &'static str {
+        ""
+    }
+}
+
+type RunGame = fn() -> Pin<Box<dyn Future<Output = ()>>>;
+
+struct Entry {
+    name: &'static str,
+    description: &'static str,
+    run: RunGame,
+}
+
+/// Menu to pick one of several games. Quitting a game from its pause menu returns to the menu.
+///
+/// ```ignore
+/// Launcher::new().add::<pong::State>().add::<convay::State>().run().await;
+/// ```
+#[derive(Default)]
+pub struct Launcher {
+    games: Vec<Entry>,
+}
+
+impl Launcher {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn add<S: GameInfo>(mut self) -> Self {
+        self.games.push(Entry {
+            name: S::name(),
+            description: S::description(),
+            run: || Box::pin(S::run_game_loop()),
+        });
+        self
+    }
+
+    /// Shows the menu until the window is closed
+    pub async fn run(self) {
+        let mut selected = 0;
+        loop {
+            if let Some(index) = self.update(&mut selected) {
+                // start and return in fresh frames, so the key that launched or quit
+                // the game isn't seen again by the game or the menu
+                next_frame().await;
+                (self.games[index].run)().await;
+                next_frame().await;
+            }
+            self.draw(selected);
+            next_frame().await;
+        }
+    }
+
+    /// Moves the selection, returns the game to start
+    fn update(&self, selected: &mut usize) -> Option<usize> {
+        if is_key_pressed(KeyCode::Up) {
+            *selected = selected.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            *selected = (*selected + 1).min(self.games.len().saturating_sub(1));
+        }
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+            return self.games.get(*selected).map(|_| *selected);
+        }
+        let mouse = Vec2::from(mouse_position());
+        let hovered = (0..self.games.len()).find(|i| entry_rect(*i).contains(mouse));
+        if let Some(i) = hovered {
+            *selected = i;
+            if is_mouse_button_pressed(MouseButton::Left) {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    fn draw(&self, selected: usize) {
+        clear_background(BLACK);
+        let w = screen_width();
+        let th = TEXT_HEIGHT * screen_height();
+        draw_text_centered("Games", w / 2.0, th * 1.5, th * 1.5, WHITE);
+        for (i, game) in self.games.iter().enumerate() {
+            let rect = entry_rect(i);
+            let color = if i == selected { YELLOW } else { WHITE };
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, color);
+            let center = rect.center();
+            draw_text_centered(game.name, center.x, center.y - th * 0.4, th, color);
+            draw_text_centered(
+                game.description,
+                center.x,
+                center.y + th * 0.5,
+                th * 0.6,
+                GRAY,
+            );
+        }
+        let hint = "Up/Down: select, Enter: play, pause menu: back to this list";
+        draw_text_centered(hint, w / 2.0, screen_height() - th, th * 0.6, GRAY);
+    }
+}
+
+/// Area of the i-th game in the menu
+fn entry_rect(i: usize) -> Rect {
+    let w = screen_width();
+    let h = screen_height();
+    let entry_h = TEXT_HEIGHT * h * 2.5;
+    Rect::new(
+        w * 0.2,
+        TEXT_HEIGHT * h * 3.5 + i as f32 * entry_h * 1.2,
+        w * 0.6,
+        entry_h,
+    )
+}
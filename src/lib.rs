@@ -1,88 +1,258 @@
 #![allow(clippy::cast_possible_truncation, clippy::missing_panics_doc)]
 
+mod audio;
+mod capture;
+mod console;
 #[cfg(not(target_arch = "wasm32"))]
 mod context;
 #[cfg(target_arch = "wasm32")]
 mod context_wasm;
+mod debug;
+mod file;
+mod frame;
+mod gamepad;
+#[cfg(not(target_arch = "wasm32"))]
+mod harness;
+mod input;
+#[cfg(target_arch = "wasm32")]
+mod js_bridge;
+mod launcher;
+mod particles;
+mod pause;
+mod replay;
+mod scene;
+mod settings;
+mod storage;
+mod text;
+mod timestep;
+mod touch;
+mod ui;
+mod viewport;
 
 use std::collections::HashMap;
 
+use audio::Audio;
+pub use audio::{Envelope, SoundEffect, Waveform};
+use capture::Capture;
+use console::Console;
+pub use console::{CommandArgs, CommandFn, CompleteFn};
+use debug::DebugOverlay;
+pub use file::{FileError, OpenOptions, OpenedFile};
+use frame::FrameInput;
+use gamepad::Gamepads;
+pub use gamepad::{GamepadAxis, GamepadButton, GamepadEvent};
+#[cfg(not(target_arch = "wasm32"))]
+pub use harness::{DEFAULT_FRAME_TIME, Harness};
+use input::Actions;
+pub use input::{KeyRepeat, key_name};
+#[cfg(target_arch = "wasm32")]
+pub use js_bridge::JsPayload;
+pub use launcher::{GameInfo, Launcher};
 use macroquad::prelude::*;
+pub use particles::{Emitter, EmitterId, EmitterShape, ParticleSystem};
+pub use pause::{PauseItem, PauseScene};
+use scene::Scenes;
+pub use scene::{BoxedScene, Scene};
+use settings::Settings;
+pub use settings::{SETTING_DEBUG_OVERLAY, SETTING_FULLSCREEN, SETTING_SHOW_FPS, SETTING_VOLUME};
+pub use storage::Storage;
+pub use text::{HAlign, TextLayout, VAlign, load_font};
+use timestep::FixedStep;
+pub use touch::Gesture;
+use touch::Touches;
+use ui::Ui;
+pub use viewport::{ScaleMode, Viewport};
 pub const TEXT_HEIGHT: f32 = 0.05;
 
 #[derive(Default)]
 pub struct Context<S: GameState> {
-    pressed_start: HashMap<KeyCode, f32>,
+    pressed_start: HashMap<KeyCode, f64>,
+    key_repeat: HashMap<KeyCode, KeyRepeat>,
+    default_repeat: KeyRepeat,
+    storage: Option<Storage>,
+    actions: Actions,
+    gamepads: Gamepads,
+    touches: Touches,
+    input: FrameInput,
+    fixed_step: FixedStep,
+    scenes: Scenes<S>,
+    settings: Settings,
+    audio: Audio,
+    capture: Capture,
+    debug: DebugOverlay,
+    console: Console<S>,
+    ui: Ui,
+    quit: bool,
     pub state: S,
 }
 
-const KEY_LOOP_DELAY: f32 = 0.5;
+/// Built-in actions of the [`PauseScene`]
+pub const ACTION_PAUSE: &str = "menu_pause";
+pub const ACTION_RESET: &str = "menu_reset";
+pub const ACTION_FPS: &str = "menu_fps";
+pub const ACTION_CONTROLS: &str = "menu_controls";
+pub const ACTION_RECORD: &str = "menu_record";
+pub const ACTION_REPLAY: &str = "menu_replay";
+pub const ACTION_SCREENSHOT: &str = "menu_screenshot";
+pub const ACTION_GIF: &str = "menu_gif";
+pub const ACTION_DEBUG: &str = "menu_debug";
+pub const ACTION_CONSOLE: &str = "menu_console";
+pub const ACTION_QUIT: &str = "menu_quit";
 
 impl<S: GameState> Context<S> {
-    /// Returns true on the first press of the key and when the key is pressed for a minimum time
+    /// Returns true on the first press of the key and then repeatedly while it is held,
+    /// timed by the [`KeyRepeat`] of the key
     pub fn is_key_pressed_loop(&mut self, key: KeyCode) -> bool {
-        let start = self.pressed_start.entry(key).or_insert(f32::NAN);
-        let now = get_time() as f32;
-        if is_key_down(key) {
-            if start.is_nan() {
-                *start = now + KEY_LOOP_DELAY;
-                return true;
-            }
-            if *start < now {
-                return true;
-            }
-        } else {
-            *start = f32::NAN;
-        }
-        false
+        let repeat = self
+            .key_repeat
+            .get(&key)
+            .copied()
+            .unwrap_or(self.default_repeat);
+        let down = self.is_key_down(key);
+        let next = self.pressed_start.entry(key).or_insert(f64::NAN);
+        repeat.update(next, down, self.input.time)
+    }
+
+    /// Sets how the key repeats in [`Context::is_key_pressed_loop`]
+    pub fn set_key_repeat(&mut self, key: KeyCode, repeat: KeyRepeat) {
+        self.key_repeat.insert(key, repeat);
+    }
+
+    /// Sets the repeat of all keys and actions without their own [`KeyRepeat`]
+    pub fn set_default_repeat(&mut self, repeat: KeyRepeat) {
+        self.default_repeat = repeat;
+    }
+
+    /// Keys of the action for display, e.g. "W/Up"
+    #[must_use]
+    pub fn action_keys_text(&self, action: &str) -> String {
+        self.action_keys(action)
+            .iter()
+            .map(|key| key_name(*key))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Actions and settings of the [`PauseScene`] and the game loop
+    pub(crate) fn define_builtins(&mut self) {
+        self.define_action(ACTION_PAUSE, &[KeyCode::Escape]);
+        self.bind_action_buttons(ACTION_PAUSE, None, &[GamepadButton::Start]);
+        self.add_virtual_button(ACTION_PAUSE, "||", Rect::new(0.45, 0.0, 0.1, 0.08));
+        self.define_action(ACTION_RESET, &[KeyCode::R]);
+        self.define_action(ACTION_FPS, &[KeyCode::F]);
+        self.define_action(ACTION_CONTROLS, &[KeyCode::C]);
+        self.define_action(ACTION_RECORD, &[KeyCode::F9]);
+        self.define_action(ACTION_REPLAY, &[KeyCode::F10]);
+        self.define_action(ACTION_SCREENSHOT, &[KeyCode::F7]);
+        self.define_action(ACTION_GIF, &[KeyCode::F8]);
+        self.define_action(ACTION_DEBUG, &[KeyCode::F3]);
+        self.define_action(ACTION_CONSOLE, &[KeyCode::GraveAccent]);
+        self.define_action(ACTION_QUIT, &[KeyCode::Q]);
+        self.define_bool_setting(SETTING_SHOW_FPS, "Show FPS", false);
+        self.define_bool_setting(SETTING_DEBUG_OVERLAY, "Debug overlay", false);
+        self.define_bool_setting(SETTING_FULLSCREEN, "Fullscreen", false);
+        self.define_range_setting(SETTING_VOLUME, "Volume", 0.0, 1.0, 0.1, 0.8);
+    }
+
+    /// Ends [`GameState::run_game_loop`] after this frame,
+    /// going back to the [`Launcher`] or closing a standalone game
+    pub fn quit(&mut self) {
+        self.quit = true;
     }
 }
 
 async fn run_game_loop<S: GameState>() {
-    let mut paused = false;
-    let mut fps = false;
     let mut ctx = Context::<S>::default();
+    ctx.define_builtins();
+    S::init(&mut ctx);
+    let mut fullscreen = false;
+    let mut recording = None;
+    let mut replay: Option<replay::Replay> = None;
 
     loop {
-        let w = screen_width();
-        let h = screen_height();
-        let th = TEXT_HEIGHT * h;
+        let th = TEXT_HEIGHT * screen_height();
 
         clear_background(ctx.state.bg_color());
+        ctx.load_sounds().await;
 
-        if is_key_pressed(KeyCode::Escape) {
-            paused = !paused;
+        let wanted = ctx.setting_bool(SETTING_FULLSCREEN);
+        if wanted != fullscreen {
+            set_fullscreen(wanted);
+            fullscreen = wanted;
         }
-        if !paused {
-            S::update(&mut ctx, get_frame_time()).await;
-        }
-        ctx.state.draw();
 
-        if fps {
-            let fps = get_fps();
-            draw_text_top_right(&format!("FPS: {fps}"), w - 5.0, 5.0, th / 2.0, WHITE);
+        if let Some(mut current) = replay.take() {
+            let stop = ctx
+                .action_keys(ACTION_PAUSE)
+                .iter()
+                .any(|key| is_key_pressed(*key));
+            let start = get_time();
+            match current.next_frame() {
+                Some(input) if !stop => {
+                    ctx.begin_frame(input);
+                    ctx.update_frame().await;
+                    replay = Some(current);
+                }
+                _ => ctx.end_replay(current),
+            }
+            let updated = get_time();
+            ctx.draw_frame();
+            ctx.record_timing((updated - start) as f32, (get_time() - updated) as f32);
+            ctx.draw_debug_overlay();
+            ctx.capture_frame().await;
+            if let Some(current) = &replay {
+                let (frame, total) = current.progress();
+                let text = format!("Replay {frame}/{total}");
+                draw_text(&text, 5.0, th / 2.0 + 5.0, th / 2.0, RED);
+            }
+            next_frame().await;
+            continue;
         }
 
-        if paused {
-            draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
-            draw_text_centered("Paused", w / 2.0, h / 2.0 - th * 1.2, th, WHITE);
-            draw_text_centered("Press ESC to continue", w / 2.0, h / 2.0, th, WHITE);
-            draw_text_centered("Press R to reset", w / 2.0, h / 2.0 + th * 1.2, th, WHITE);
-            draw_text_centered(
-                "Press F to enable FPS",
-                w / 2.0,
-                h / 2.0 + th * 2.4,
-                th,
-                WHITE,
-            );
-            if is_key_pressed(KeyCode::R) {
-                ctx.state.reset();
-                paused = false;
-            }
-            if is_key_pressed(KeyCode::F) {
-                fps = !fps;
+        ctx.begin_frame(FrameInput::capture());
+        let rebinding = ctx.rebinding_action().is_some();
+        if !rebinding && ctx.is_action_pressed(ACTION_RECORD) {
+            match recording.take() {
+                Some(done) => ctx.save_recording(&done).await,
+                None => recording = Some(ctx.start_recording()),
             }
         }
+        if !rebinding && ctx.is_action_pressed(ACTION_REPLAY) {
+            recording = None;
+            replay = ctx.load_replay().await;
+            next_frame().await;
+            continue;
+        }
+        if !rebinding && ctx.is_action_pressed(ACTION_SCREENSHOT) {
+            ctx.take_screenshot();
+        }
+        if !rebinding && ctx.is_action_pressed(ACTION_GIF) {
+            ctx.toggle_gif_capture();
+        }
+        if !rebinding && ctx.is_action_pressed(ACTION_DEBUG) {
+            let show = ctx.setting_bool(SETTING_DEBUG_OVERLAY);
+            ctx.set_setting(SETTING_DEBUG_OVERLAY, if show { 0.0 } else { 1.0 });
+        }
+        if let Some(recording) = &mut recording {
+            ctx.record_frame(recording);
+        }
+        let start = get_time();
+        ctx.update_frame().await;
+        if ctx.quit {
+            return;
+        }
+        let updated = get_time();
+        ctx.draw_frame();
+        ctx.record_timing((updated - start) as f32, (get_time() - updated) as f32);
+        ctx.draw_debug_overlay();
+        ctx.capture_frame().await;
+
+        if recording.is_some() {
+            draw_text("Recording", 5.0, th / 2.0 + 5.0, th / 2.0, RED);
+        }
+        if ctx.is_capturing_gif() {
+            draw_text("GIF", 5.0, th * 1.5 + 5.0, th / 2.0, RED);
+        }
 
         next_frame().await;
     }
@@ -94,39 +264,65 @@ pub trait GameState: Default {
         ctx: &mut Context<Self>,
         delta_time: f32,
     ) -> impl std::future::Future<Output = ()> + Send;
-    fn draw(&self);
-    /// If this returns true, the update will be skipped
+    /// `alpha` is how far the time has advanced from the last update towards the next one
+    /// in fixed timestep mode, see [`GameState::tick_rate`], and always 1 otherwise
+    fn draw(&self, alpha: f32);
+    /// If this returns true, [`GameState::update_paused`] is called instead of the update
     fn is_paused(&self) -> bool;
+    /// Update while [`GameState::is_paused`] is true, once per frame with the frame time.
+    /// Does nothing by default, games can handle the input that ends their pause here.
+    fn update_paused(
+        _ctx: &mut Context<Self>,
+        _delta_time: f32,
+    ) -> impl std::future::Future<Output = ()> + Send {
+        async {}
+    }
+    /// Entries shown in the pause menu between the built-in ones
+    fn pause_items(&self) -> Vec<PauseItem<Self>> {
+        Vec::new()
+    }
     fn reset(&mut self);
+    /// Updates per second for a fixed timestep. If set, [`GameState::update`] is called
+    /// with a delta of `1 / tick_rate` as many times as fit into the elapsed time,
+    /// so the simulation behaves the same at any frame rate.
+    /// Defaults to `None`, one update per frame with the frame time.
+    fn tick_rate() -> Option<f32> {
+        None
+    }
+    /// Virtual resolution the game draws in, scaled to any screen size.
+    /// Defaults to `None`, drawing in screen pixels.
+    fn viewport() -> Option<Viewport> {
+        None
+    }
+    /// Called once before the first update, e.g. to load values from [`Context::storage`]
+    fn init(_ctx: &mut Context<Self>) {}
+    /// Name of the [`Storage`] of this game, defaults to the name of the crate
+    fn storage_name() -> &'static str {
+        std::any::type_name::<Self>()
+            .split("::")
+            .next()
+            .unwrap_or("game")
+    }
     #[must_use]
     fn run_game_loop() -> impl Future<Output = ()> {
         run_game_loop::<Self>()
     }
 }
 
+/// Draws a single line of text centered on `x`, `y`, see [`TextLayout`] for more options
 pub fn draw_text_centered(text: &str, x: f32, y: f32, size: f32, color: Color) {
-    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-    let font_size = size as u16;
-    let dimensions = measure_text(text, None, font_size, 1.0);
-    draw_text(
-        text,
-        x - dimensions.width / 2.0,
-        y + dimensions.height / 2.0,
-        size,
-        color,
-    );
+    TextLayout::new(text)
+        .size(size)
+        .color(color)
+        .align(HAlign::Center, VAlign::Middle)
+        .draw(x, y);
 }
 
 /// Draw text with the given position being at the top right corner of the text
 pub fn draw_text_top_right(text: &str, w: f32, h: f32, size: f32, white: Color) {
-    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-    let font_size = size as u16;
-    let dimensions = measure_text(text, None, font_size, 1.0);
-    draw_text(
-        text,
-        w - dimensions.width,
-        h + dimensions.height,
-        size,
-        white,
-    );
+    TextLayout::new(text)
+        .size(size)
+        .color(white)
+        .align(HAlign::Right, VAlign::Top)
+        .draw(w, h);
 }
@@ -0,0 +1,321 @@
+use std::f32::consts::{PI, TAU};
+
+use macroquad::{prelude::*, rand};
+
+/// Particles alive at once, new ones are dropped above this
+const MAX_PARTICLES: usize = 10_000;
+/// Particles per mesh, macroquad draws at most 5000 indices at once and each particle takes 6
+const BATCH: usize = 800;
+
+/// Area in which an [`Emitter`] spawns particles, relative to its position
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EmitterShape {
+    #[default]
+    Point,
+    /// Anywhere inside a circle around the position
+    Circle { radius: f32 },
+    /// Anywhere inside a rectangle centered on the position
+    Rect { size: Vec2 },
+    /// Anywhere on the line from the position to the position plus `offset`
+    Line { offset: Vec2 },
+}
+
+impl EmitterShape {
+    fn sample(self) -> Vec2 {
+        match self {
+            EmitterShape::Point => Vec2::ZERO,
+            EmitterShape::Circle { radius } => {
+                // sqrt to spread them evenly instead of bunching up in the middle
+                let distance = radius * rand::gen_range(0.0f32, 1.0).sqrt();
+                Vec2::from_angle(rand::gen_range(0.0, TAU)) * distance
+            }
+            EmitterShape::Rect { size } => {
+                let half = size / 2.0;
+                vec2(
+                    rand::gen_range(-half.x, half.x),
+                    rand::gen_range(-half.y, half.y),
+                )
+            }
+            EmitterShape::Line { offset } => offset * rand::gen_range(0.0, 1.0),
+        }
+    }
+}
+
+/// How particles are spawned and how they change over their life.
+///
+/// ```ignore
+/// let sparks = Emitter::new()
+///     .speed(100.0, 300.0)
+///     .direction(0.0, PI / 2.0)
+///     .lifetime(0.2, 0.5)
+///     .gravity(vec2(0.0, 400.0))
+///     .color(YELLOW, Color::new(1.0, 0.0, 0.0, 0.0))
+///     .size(4.0, 1.0);
+/// particles.burst(&sparks, position, 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+    shape: EmitterShape,
+    /// Seconds a particle lives, picked between the two
+    lifetime: (f32, f32),
+    /// Units per second at the start, picked between the two
+    speed: (f32, f32),
+    /// Angle in radians the particles move towards, 0 is to the right
+    direction: f32,
+    /// Angle in radians the direction varies in both ways
+    spread: f32,
+    gravity: Vec2,
+    /// Colors at the start and the end of the life
+    color: (Color, Color),
+    /// Sizes at the start and the end of the life
+    size: (f32, f32),
+    /// Particles per second of emitters added with [`ParticleSystem::add_emitter`]
+    rate: f32,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emitter {
+    /// White particles of size 4 living for a second, flying in all directions from a point
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            shape: EmitterShape::Point,
+            lifetime: (1.0, 1.0),
+            speed: (50.0, 100.0),
+            direction: 0.0,
+            spread: PI,
+            gravity: Vec2::ZERO,
+            color: (WHITE, WHITE),
+            size: (4.0, 4.0),
+            rate: 10.0,
+        }
+    }
+
+    #[must_use]
+    pub fn shape(mut self, shape: EmitterShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Seconds a particle lives, a random value between `min` and `max`
+    #[must_use]
+    pub fn lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    /// Starting speed in units per second, a random value between `min` and `max`
+    #[must_use]
+    pub fn speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    /// Particles fly towards `angle` in radians, varied by up to `spread` to both sides.
+    /// A spread of `PI` sends them in all directions, the default.
+    #[must_use]
+    pub fn direction(mut self, angle: f32, spread: f32) -> Self {
+        self.direction = angle;
+        self.spread = spread;
+        self
+    }
+
+    /// Acceleration in units per second squared, e.g. `vec2(0.0, 500.0)` to fall down
+    #[must_use]
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Color fading from `start` to `end` over the life, use a transparent end to fade out
+    #[must_use]
+    pub fn color(mut self, start: Color, end: Color) -> Self {
+        self.color = (start, end);
+        self
+    }
+
+    /// Width of the square particles changing from `start` to `end` over the life
+    #[must_use]
+    pub fn size(mut self, start: f32, end: f32) -> Self {
+        self.size = (start, end);
+        self
+    }
+
+    /// Particles per second when running continuously, see [`ParticleSystem::add_emitter`]
+    #[must_use]
+    pub fn rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    fn spawn(&self, position: Vec2) -> Particle {
+        let angle = self.direction + rand::gen_range(-self.spread, self.spread);
+        let speed = rand::gen_range(self.speed.0, self.speed.1);
+        Particle {
+            position: position + self.shape.sample(),
+            velocity: Vec2::from_angle(angle) * speed,
+            age: 0.0,
+            lifetime: rand::gen_range(self.lifetime.0, self.lifetime.1),
+            gravity: self.gravity,
+            color: self.color,
+            size: self.size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    gravity: Vec2,
+    color: (Color, Color),
+    size: (f32, f32),
+}
+
+impl Particle {
+    /// Part of the life that has passed, from 0 to 1
+    fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Identifies an emitter added with [`ParticleSystem::add_emitter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmitterId(u32);
+
+/// An emitter that spawns particles every update
+#[derive(Debug, Clone, Copy)]
+struct RunningEmitter {
+    id: EmitterId,
+    emitter: Emitter,
+    position: Vec2,
+    /// Particles owed from earlier updates, so low rates still spawn
+    pending: f32,
+}
+
+/// Particles and the emitters that spawn them continuously.
+///
+/// Keep it in the game state, call [`ParticleSystem::update`] with the delta time
+/// of the update and [`ParticleSystem::draw`] in the draw. Positions are in the units
+/// the game draws in.
+#[derive(Debug, Clone, Default)]
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    emitters: Vec<RunningEmitter>,
+    next_id: u32,
+}
+
+impl ParticleSystem {
+    /// Spawns `count` particles at once at `position`
+    pub fn burst(&mut self, emitter: &Emitter, position: Vec2, count: usize) {
+        let count = count.min(MAX_PARTICLES.saturating_sub(self.particles.len()));
+        self.particles
+            .extend((0..count).map(|_| emitter.spawn(position)));
+    }
+
+    /// Spawns particles at `position` at the rate of the emitter until it is removed
+    pub fn add_emitter(&mut self, emitter: Emitter, position: Vec2) -> EmitterId {
+        let id = EmitterId(self.next_id);
+        self.next_id += 1;
+        self.emitters.push(RunningEmitter {
+            id,
+            emitter,
+            position,
+            pending: 0.0,
+        });
+        id
+    }
+
+    /// Moves a running emitter, e.g. to follow an object
+    pub fn move_emitter(&mut self, id: EmitterId, position: Vec2) {
+        if let Some(running) = self.emitters.iter_mut().find(|e| e.id == id) {
+            running.position = position;
+        }
+    }
+
+    /// Stops an emitter, the particles it spawned live on
+    pub fn remove_emitter(&mut self, id: EmitterId) {
+        self.emitters.retain(|e| e.id != id);
+    }
+
+    /// Removes all particles and emitters
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.emitters.clear();
+    }
+
+    /// Number of living particles
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Spawns particles of the running emitters, moves all particles and removes the old ones
+    pub fn update(&mut self, delta_time: f32) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for running in &mut emitters {
+            running.pending += running.emitter.rate * delta_time;
+            let count = running.pending.floor();
+            running.pending -= count;
+            self.burst(&running.emitter, running.position, count as usize);
+        }
+        self.emitters = emitters;
+
+        for particle in &mut self.particles {
+            particle.age += delta_time;
+            particle.velocity += particle.gravity * delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Draws all particles as squares, a mesh per batch of particles
+    pub fn draw(&self) {
+        for batch in self.particles.chunks(BATCH) {
+            let mut mesh = Mesh {
+                vertices: Vec::with_capacity(batch.len() * 4),
+                indices: Vec::with_capacity(batch.len() * 6),
+                texture: None,
+            };
+            for particle in batch {
+                let t = particle.progress();
+                let (start, end) = particle.color;
+                let color = Color::from_vec(start.to_vec().lerp(end.to_vec(), t));
+                let half = (particle.size.0 + (particle.size.1 - particle.size.0) * t) / 2.0;
+                let Vec2 { x, y } = particle.position;
+                let first = mesh.vertices.len() as u16;
+                for (corner_x, corner_y) in
+                    [(-half, -half), (half, -half), (half, half), (-half, half)]
+                {
+                    mesh.vertices.push(Vertex::new(
+                        x + corner_x,
+                        y + corner_y,
+                        0.0,
+                        0.0,
+                        0.0,
+                        color,
+                    ));
+                }
+                mesh.indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+            }
+            draw_mesh(&mesh);
+        }
+    }
+}
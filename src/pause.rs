@@ -0,0 +1,211 @@
+use macroquad::prelude::*;
+
+use crate::{
+    ACTION_CONTROLS, ACTION_DEBUG, ACTION_FPS, ACTION_GIF, ACTION_PAUSE, ACTION_QUIT,
+    ACTION_RECORD, ACTION_REPLAY, ACTION_RESET, ACTION_SCREENSHOT, Context, GameState,
+    SETTING_SHOW_FPS, TEXT_HEIGHT, draw_text_centered, scene::Scene, settings::SettingsScene,
+};
+
+/// Entry a game adds to the pause menu with [`GameState::pause_items`]
+pub struct PauseItem<S: GameState> {
+    label: String,
+    on_select: fn(&mut Context<S>),
+}
+
+impl<S: GameState> PauseItem<S> {
+    /// The menu stays open after `on_select` ran, call [`Context::clear_scenes`] to continue the game
+    pub fn new(label: impl Into<String>, on_select: fn(&mut Context<S>)) -> Self {
+        Self {
+            label: label.into(),
+            on_select,
+        }
+    }
+}
+
+/// Built-in scene shown over the game when the pause action is pressed.
+///
+/// Lists the built-in entries and the [`GameState::pause_items`] of the game.
+/// Entries are selected with Up/Down and Enter or by clicking,
+/// the built-in ones also have their own actions.
+#[derive(Default)]
+pub struct PauseScene {
+    selected: usize,
+}
+
+impl PauseScene {
+    /// Screen area of the i-th of `count` entries
+    fn item_rect<S: GameState>(ctx: &Context<S>, i: usize, count: usize) -> Rect {
+        let w = ctx.screen_width();
+        let h = ctx.screen_height();
+        let th = TEXT_HEIGHT * h;
+        let top = h / 2.0 - th * 1.2 * count as f32 / 2.0;
+        Rect::new(w * 0.25, top + th * 1.2 * i as f32, w * 0.5, th * 1.2)
+    }
+}
+
+fn items<S: GameState>(ctx: &Context<S>) -> Vec<PauseItem<S>> {
+    let with_keys =
+        |label: &str, action: &str| format!("{label} ({})", ctx.action_keys_text(action));
+    let fps = if ctx.setting_bool(SETTING_SHOW_FPS) {
+        "Hide FPS"
+    } else {
+        "Show FPS"
+    };
+    let gif = if ctx.is_capturing_gif() {
+        "Stop GIF"
+    } else {
+        "Record GIF"
+    };
+    let mut items: Vec<PauseItem<S>> = vec![
+        PauseItem::new(with_keys("Continue", ACTION_PAUSE), |ctx| ctx.pop_scene()),
+        PauseItem::new(with_keys("Reset", ACTION_RESET), |ctx| {
+            ctx.state.reset();
+            ctx.pop_scene();
+        }),
+        PauseItem::new(with_keys(fps, ACTION_FPS), |ctx| {
+            let show = ctx.setting_bool(SETTING_SHOW_FPS);
+            ctx.set_setting(SETTING_SHOW_FPS, if show { 0.0 } else { 1.0 });
+        }),
+        PauseItem::new(with_keys("Controls", ACTION_CONTROLS), |ctx| {
+            ctx.replace_scene(ControlsScene::default());
+        }),
+        PauseItem::new("Settings", |ctx| {
+            ctx.replace_scene(SettingsScene::default());
+        }),
+        // both close the menu first, so it is not captured
+        PauseItem::new(with_keys("Screenshot", ACTION_SCREENSHOT), |ctx| {
+            ctx.pop_scene();
+            ctx.take_screenshot();
+        }),
+        PauseItem::new(with_keys(gif, ACTION_GIF), |ctx| {
+            ctx.pop_scene();
+            ctx.toggle_gif_capture();
+        }),
+    ];
+    items.extend(ctx.state.pause_items());
+    items.push(PauseItem::new(with_keys("Quit", ACTION_QUIT), |ctx| {
+        ctx.quit();
+    }));
+    items
+}
+
+impl<S: GameState> Scene<S> for PauseScene {
+    fn update(&mut self, ctx: &mut Context<S>, _delta_time: f32) {
+        let items = items(ctx);
+        if ctx.is_key_pressed(KeyCode::Up) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if ctx.is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1).min(items.len() - 1);
+        }
+        // the selected entry has the keyboard, Enter activates it
+        ctx.set_ui_focus(Some(self.selected));
+        let mut chosen = None;
+        for (i, item) in items.iter().enumerate() {
+            let rect = PauseScene::item_rect(ctx, i, items.len());
+            if ctx.ui_button(rect, &item.label) {
+                chosen = Some(i);
+            }
+        }
+        if let Some(i) = chosen {
+            self.selected = i;
+            (items[i].on_select)(ctx);
+            return;
+        }
+
+        let hotkeys: [(&str, usize); 5] = [
+            (ACTION_PAUSE, 0),
+            (ACTION_RESET, 1),
+            (ACTION_FPS, 2),
+            (ACTION_CONTROLS, 3),
+            (ACTION_QUIT, items.len() - 1),
+        ];
+        if let Some((_, i)) = hotkeys
+            .into_iter()
+            .find(|(action, _)| ctx.is_action_pressed(action))
+        {
+            (items[i].on_select)(ctx);
+        }
+    }
+
+    fn draw(&self, ctx: &Context<S>) {
+        let w = screen_width();
+        let h = screen_height();
+        let th = TEXT_HEIGHT * h;
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
+        let items = items(ctx);
+        let first = PauseScene::item_rect(ctx, 0, items.len());
+        draw_text_centered("Paused", w / 2.0, first.y - th * 1.5, th * 1.2, WHITE);
+        let last = PauseScene::item_rect(ctx, items.len() - 1, items.len());
+        let hint = format!(
+            "{}: record, {}: replay, {}: debug overlay",
+            ctx.action_keys_text(ACTION_RECORD),
+            ctx.action_keys_text(ACTION_REPLAY),
+            ctx.action_keys_text(ACTION_DEBUG)
+        );
+        draw_text_centered(&hint, w / 2.0, last.bottom() + th, th * 0.6, GRAY);
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
+
+/// List of all actions, where the selected one can be bound to a new key.
+/// Replaces the [`PauseScene`] while it is open.
+#[derive(Default)]
+struct ControlsScene {
+    selected: usize,
+}
+
+impl<S: GameState> Scene<S> for ControlsScene {
+    fn update(&mut self, ctx: &mut Context<S>, _delta_time: f32) {
+        if ctx.update_rebinding() {
+            return;
+        }
+        let actions: Vec<String> = ctx
+            .actions()
+            .map(|(action, _)| action.to_string())
+            .collect();
+        if ctx.is_action_pressed(ACTION_PAUSE) {
+            ctx.replace_scene(PauseScene::default());
+        }
+        if ctx.is_key_pressed(KeyCode::Up) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if ctx.is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1).min(actions.len().saturating_sub(1));
+        }
+        if ctx.is_key_pressed(KeyCode::Enter)
+            && let Some(action) = actions.get(self.selected)
+        {
+            ctx.rebind_action(action);
+        }
+    }
+
+    fn draw(&self, ctx: &Context<S>) {
+        let w = screen_width();
+        let h = screen_height();
+        let th = TEXT_HEIGHT * h * 0.75;
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
+        let hint = if ctx.rebinding_action().is_some() {
+            "Press the new key".to_string()
+        } else {
+            format!(
+                "Up/Down: select, Enter: rebind, {}: back",
+                ctx.action_keys_text(ACTION_PAUSE)
+            )
+        };
+        draw_text_centered(&hint, w / 2.0, th * 1.5, th, WHITE);
+        for (i, (action, _)) in ctx.actions().enumerate() {
+            let color = if i == self.selected { YELLOW } else { WHITE };
+            let y = th * (3.0 + i as f32 * 1.2);
+            let keys = ctx.action_keys_text(action);
+            draw_text_centered(&format!("{action}: {keys}"), w / 2.0, y, th, color);
+        }
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
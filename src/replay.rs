@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use macroquad::{miniquad::date, prelude::*, rand};
+
+use crate::{
+    Context, GameState, GamepadButton, KeyRepeat, OpenOptions,
+    frame::FrameInput,
+    input::{Actions, Bindings, parse_key},
+    settings::Settings,
+    storage::{Storage, escape, unescape},
+};
+
+const HEADER: &str = "macroquad_stuff recording 2";
+
+/// Input of every updated frame together with everything else needed to replay it:
+/// the random seed the state was created with, the values of the [`Storage`] that
+/// `init` and the settings read, and the key and button bindings at the time.
+pub(crate) struct Recording {
+    seed: u64,
+    storage: BTreeMap<String, String>,
+    bindings: Bindings,
+    frames: Vec<FrameInput>,
+}
+
+impl Recording {
+    fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\nseed {}\n", self.seed);
+        for (key, value) in &self.storage {
+            text.push_str(&format!("storage {}\t{}\n", escape(key), escape(value)));
+        }
+        for (action, keys) in &self.bindings.keys {
+            let keys: Vec<String> = keys.iter().map(|key| format!("{key:?}")).collect();
+            text.push_str(&format!("bind {action} {}\n", keys.join(",")));
+        }
+        for (action, (gamepad, buttons)) in &self.bindings.buttons {
+            let gamepad = gamepad.map_or_else(|| "any".to_string(), |n| n.to_string());
+            let buttons: Vec<String> = buttons.iter().map(|b| format!("{b:?}")).collect();
+            text.push_str(&format!(
+                "buttons {action} {gamepad} {}\n",
+                buttons.join(",")
+            ));
+        }
+        for frame in &self.frames {
+            text.push_str("frame ");
+            text.push_str(&frame.to_line());
+            text.push('\n');
+        }
+        text
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let mut recording = Recording {
+            seed: 0,
+            storage: BTreeMap::new(),
+            bindings: Bindings::default(),
+            frames: Vec::new(),
+        };
+        for line in lines {
+            let (kind, rest) = line.split_once(' ')?;
+            match kind {
+                "seed" => recording.seed = rest.parse().ok()?,
+                "storage" => {
+                    let (key, value) = rest.split_once('\t')?;
+                    recording.storage.insert(unescape(key), unescape(value));
+                }
+                "bind" => {
+                    let (action, keys) = rest.split_once(' ').unwrap_or((rest, ""));
+                    let keys = keys.split(',').filter_map(parse_key).collect();
+                    recording.bindings.keys.insert(action.to_string(), keys);
+                }
+                "buttons" => {
+                    let mut parts = rest.splitn(3, ' ');
+                    let action = parts.next()?;
+                    let gamepad = match parts.next()? {
+                        "any" => None,
+                        n => Some(n.parse().ok()?),
+                    };
+                    let buttons = parts
+                        .next()
+                        .unwrap_or_default()
+                        .split(',')
+                        .filter_map(GamepadButton::parse)
+                        .collect();
+                    let binding = (gamepad, buttons);
+                    recording
+                        .bindings
+                        .buttons
+                        .insert(action.to_string(), binding);
+                }
+                "frame" => recording.frames.push(FrameInput::from_line(rest)?),
+                _ => return None,
+            }
+        }
+        Some(recording)
+    }
+}
+
+/// A recording being played back
+pub(crate) struct Replay {
+    frames: std::vec::IntoIter<FrameInput>,
+    total: usize,
+    /// Storage of the player, the replay runs on a copy of the recorded one in memory
+    saved_storage: Option<Storage>,
+}
+
+impl Replay {
+    /// Input of the next frame, `None` once the recording is over
+    pub(crate) fn next_frame(&mut self) -> Option<FrameInput> {
+        self.frames.next()
+    }
+
+    pub(crate) fn progress(&self) -> (usize, usize) {
+        (self.total - self.frames.len(), self.total)
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Recreates the state with a fixed random seed, so a replay starts exactly like the recording.
+    /// Actions, settings, virtual buttons and commands are defined again from scratch.
+    /// Sounds are kept, adding the same sound again doesn't reload it.
+    pub(crate) fn restart(&mut self, seed: u64) {
+        rand::srand(seed);
+        self.state = S::default();
+        self.pressed_start.clear();
+        self.key_repeat.clear();
+        self.default_repeat = KeyRepeat::default();
+        self.actions = Actions::default();
+        self.settings = Settings::default();
+        self.touches.clear_buttons();
+        self.console.clear_commands();
+        self.gamepads.clear_repeat();
+        self.fixed_step.reset();
+        self.scenes.clear();
+        self.define_builtins();
+        S::init(self);
+    }
+
+    /// Restarts the game and starts recording every updated frame
+    pub(crate) fn start_recording(&mut self) -> Recording {
+        let seed = (date::now() * 1000.0) as u64;
+        let storage = self.storage().values();
+        self.restart(seed);
+        info!("Recording with seed {}", seed);
+        Recording {
+            seed,
+            storage,
+            bindings: self.bindings(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub(crate) fn record_frame(&self, recording: &mut Recording) {
+        recording.frames.push(self.input.clone());
+    }
+
+    pub(crate) async fn save_recording(&self, recording: &Recording) {
+        info!("Saving recording of {} frames", recording.frames.len());
+        let text = recording.to_text();
+        if let Err(e) = self.save_file("recording.txt", text.as_bytes()).await {
+            warn!("Failed to save recording: {}", e);
+        }
+    }
+
+    /// Lets the player pick a recording and restarts the game to replay it.
+    /// The replay reads and writes a copy of the recorded storage, never the player's.
+    pub(crate) async fn load_replay(&mut self) -> Option<Replay> {
+        let options = OpenOptions::new()
+            .title("Open recording")
+            .filter("Recordings", &["txt"]);
+        let file = match self.open_file_with(&options).await {
+            Ok(file) => file?,
+            Err(e) => {
+                warn!("Failed to open recording: {}", e);
+                return None;
+            }
+        };
+        let Some(recording) = Recording::parse(&file.text()) else {
+            warn!("{} is not a valid recording", file.name);
+            return None;
+        };
+        info!("Replaying {} frames", recording.frames.len());
+        let storage = Storage::in_memory(S::storage_name(), recording.storage);
+        let saved_storage = self.storage.replace(storage);
+        self.restart(recording.seed);
+        self.set_bindings(recording.bindings);
+        Some(Replay {
+            total: recording.frames.len(),
+            frames: recording.frames.into_iter(),
+            saved_storage,
+        })
+    }
+
+    /// Gives the player's storage back and restarts the game with their settings and bindings
+    pub(crate) fn end_replay(&mut self, replay: Replay) {
+        info!("Replay finished");
+        self.storage = replay.saved_storage;
+        self.restart((date::now() * 1000.0) as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_survives_a_text_round_trip() {
+        let mut bindings = Bindings::default();
+        bindings
+            .keys
+            .insert("jump".to_string(), vec![KeyCode::Space, KeyCode::W]);
+        bindings.keys.insert("unbound".to_string(), Vec::new());
+        bindings
+            .buttons
+            .insert("jump".to_string(), (Some(1), vec![GamepadButton::South]));
+        bindings
+            .buttons
+            .insert("fire".to_string(), (None, vec![GamepadButton::East]));
+        let frame = FrameInput {
+            time: 0.5,
+            keys_pressed: [KeyCode::Space].into(),
+            chars: vec!['x'],
+            ..Default::default()
+        };
+        let recording = Recording {
+            seed: 42,
+            storage: BTreeMap::from([
+                ("setting.volume".to_string(), "0.5".to_string()),
+                ("note".to_string(), "tab\tand\nnewline".to_string()),
+            ]),
+            bindings,
+            frames: vec![frame.clone(), frame],
+        };
+        let text = recording.to_text();
+        let parsed = Recording::parse(&text).unwrap();
+        assert_eq!(parsed.seed, 42);
+        assert_eq!(parsed.storage, recording.storage);
+        assert_eq!(parsed.bindings.keys, recording.bindings.keys);
+        assert_eq!(parsed.bindings.buttons, recording.bindings.buttons);
+        assert_eq!(parsed.frames.len(), 2);
+        assert_eq!(parsed.to_text(), text);
+    }
+
+    #[test]
+    fn recording_of_another_version_is_rejected() {
+        assert!(Recording::parse("macroquad_stuff recording 1\nseed 1\n").is_none());
+        assert!(Recording::parse(&format!("{HEADER}\nunknown line\n")).is_none());
+    }
+}
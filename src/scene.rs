@@ -0,0 +1,146 @@
+use macroquad::prelude::*;
+
+use crate::{
+    ACTION_CONSOLE, ACTION_PAUSE, Context, GameState, SETTING_SHOW_FPS, TEXT_HEIGHT,
+    console::ConsoleScene, draw_text_top_right, pause::PauseScene,
+};
+
+/// A screen shown on top of the game, like a title screen, a menu or a game-over screen.
+///
+/// Scenes live on a stack in the [`Context`]. Only the top scene is updated,
+/// the game itself runs while the stack is empty.
+pub trait Scene<S: GameState> {
+    fn update(&mut self, ctx: &mut Context<S>, delta_time: f32);
+    fn draw(&self, ctx: &Context<S>);
+    /// If this returns true, the scenes below and the game are drawn first
+    fn is_transparent(&self) -> bool {
+        false
+    }
+}
+
+/// Boxed scene as it is kept on the stack
+pub type BoxedScene<S> = Box<dyn Scene<S> + Send + Sync>;
+
+enum SceneChange<S: GameState> {
+    Push(BoxedScene<S>),
+    Pop,
+    Replace(BoxedScene<S>),
+    Clear,
+}
+
+#[derive(Default)]
+pub(crate) struct Scenes<S: GameState> {
+    stack: Vec<BoxedScene<S>>,
+    /// Changes requested during the update, applied after it
+    changes: Vec<SceneChange<S>>,
+}
+
+impl<S: GameState> Scenes<S> {
+    pub(crate) fn clear(&mut self) {
+        self.stack.clear();
+        self.changes.clear();
+    }
+
+    fn apply_changes(&mut self) {
+        for change in std::mem::take(&mut self.changes) {
+            match change {
+                SceneChange::Push(scene) => self.stack.push(scene),
+                SceneChange::Pop => {
+                    self.stack.pop();
+                }
+                SceneChange::Replace(scene) => {
+                    self.stack.pop();
+                    self.stack.push(scene);
+                }
+                SceneChange::Clear => self.stack.clear(),
+            }
+        }
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Shows `scene` on top of the current one after this update
+    pub fn push_scene(&mut self, scene: impl Scene<S> + Send + Sync + 'static) {
+        self.scenes.changes.push(SceneChange::Push(Box::new(scene)));
+    }
+
+    /// Removes the top scene after this update
+    pub fn pop_scene(&mut self) {
+        self.scenes.changes.push(SceneChange::Pop);
+    }
+
+    /// Swaps the top scene for `scene` after this update, e.g. to go from a title screen to a menu
+    pub fn replace_scene(&mut self, scene: impl Scene<S> + Send + Sync + 'static) {
+        self.scenes
+            .changes
+            .push(SceneChange::Replace(Box::new(scene)));
+    }
+
+    /// Removes all scenes after this update, going back to the game
+    pub fn clear_scenes(&mut self) {
+        self.scenes.changes.push(SceneChange::Clear);
+    }
+
+    /// Number of scenes above the game
+    #[must_use]
+    pub fn scene_count(&self) -> usize {
+        self.scenes.stack.len()
+    }
+
+    /// Updates the top scene, or the game if there is none.
+    /// The pause action opens the built-in [`PauseScene`] over the game
+    /// and the console action the console.
+    /// While [`GameState::is_paused`] is true, [`GameState::update_paused`] runs instead of the update.
+    pub(crate) async fn update_frame(&mut self) {
+        if let Some(mut scene) = self.scenes.stack.pop() {
+            let delta = self.frame_time();
+            // layer 0 is the game
+            self.begin_ui(self.scenes.stack.len() + 1);
+            scene.update(self, delta);
+            self.scenes.stack.push(scene);
+        } else if self.is_action_pressed(ACTION_PAUSE) {
+            self.push_scene(PauseScene::default());
+        } else if self.is_action_pressed(ACTION_CONSOLE) {
+            self.push_scene(ConsoleScene);
+        } else if self.state.is_paused() {
+            let delta = self.frame_time();
+            self.begin_ui(0);
+            S::update_paused(self, delta).await;
+        } else {
+            self.update_state().await;
+        }
+        self.scenes.apply_changes();
+        self.ui.truncate(self.scenes.stack.len() + 1);
+    }
+
+    /// Draws the game and the scenes that are visible, starting from the lowest one
+    pub(crate) fn draw_frame(&self) {
+        let stack = &self.scenes.stack;
+        let first = stack
+            .iter()
+            .rposition(|scene| !scene.is_transparent())
+            .unwrap_or(0);
+        if stack.iter().all(|scene| scene.is_transparent()) {
+            self.draw_state();
+            self.ui.draw(0);
+            // the virtual buttons belong to the game, scenes get no input from them
+            self.touches.draw();
+        }
+        for (i, scene) in stack.iter().enumerate().skip(first) {
+            scene.draw(self);
+            self.ui.draw(i + 1);
+        }
+
+        if self.setting_bool(SETTING_SHOW_FPS) {
+            let th = TEXT_HEIGHT * screen_height();
+            let fps = get_fps();
+            draw_text_top_right(
+                &format!("FPS: {fps}"),
+                screen_width() - 5.0,
+                5.0,
+                th / 2.0,
+                WHITE,
+            );
+        }
+    }
+}
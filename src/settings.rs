@@ -0,0 +1,313 @@
+use macroquad::prelude::*;
+
+use crate::{
+    ACTION_PAUSE, Context, GameState, TEXT_HEIGHT, draw_text_centered, pause::PauseScene,
+    scene::Scene,
+};
+
+/// Built-in settings
+pub const SETTING_SHOW_FPS: &str = "show_fps";
+pub const SETTING_FULLSCREEN: &str = "fullscreen";
+/// Frame time graph and [`Context::debug_watch`] values
+pub const SETTING_DEBUG_OVERLAY: &str = "debug_overlay";
+/// Master volume of all sounds, from 0 to 1
+pub const SETTING_VOLUME: &str = "volume";
+
+/// Type and range of a setting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SettingKind {
+    Bool,
+    /// A number from `min` to `max` in steps of `step`
+    Range {
+        min: f32,
+        max: f32,
+        step: f32,
+    },
+    /// One of the options, stored as its index
+    Choice(&'static [&'static str]),
+}
+
+impl SettingKind {
+    /// Brings `value` into the range of the kind
+    fn clamp(self, value: f32) -> f32 {
+        match self {
+            SettingKind::Bool => {
+                if value == 0.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            SettingKind::Range { min, max, step } => {
+                let steps = ((value - min) / step).round();
+                (min + steps * step).clamp(min, max)
+            }
+            SettingKind::Choice(options) => value
+                .round()
+                .clamp(0.0, options.len().saturating_sub(1) as f32),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Setting {
+    key: String,
+    label: String,
+    kind: SettingKind,
+    value: f32,
+    /// Set when the setting is defined or its value changes, see [`Context::take_setting_change`]
+    changed: bool,
+}
+
+impl Setting {
+    fn value_text(&self) -> String {
+        match self.kind {
+            SettingKind::Bool => if self.value == 0.0 { "off" } else { "on" }.to_string(),
+            SettingKind::Range { step, .. } if step.fract() == 0.0 => {
+                format!("{}", self.value as i64)
+            }
+            SettingKind::Range { .. } => format!("{:.2}", self.value),
+            SettingKind::Choice(options) => options[self.value as usize].to_string(),
+        }
+    }
+
+    /// Value after going `direction` steps, wrapping around for bools and choices
+    fn stepped(&self, direction: i32) -> f32 {
+        match self.kind {
+            SettingKind::Bool => 1.0 - self.value,
+            SettingKind::Range { step, .. } => self.value + step * direction as f32,
+            SettingKind::Choice(options) => {
+                let count = options.len() as i32;
+                (self.value as i32 + direction).rem_euclid(count) as f32
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Settings {
+    list: Vec<Setting>,
+}
+
+impl Settings {
+    fn get(&self, key: &str) -> Option<&Setting> {
+        self.list.iter().find(|setting| setting.key == key)
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Declares a setting that is shown in the settings menu.
+    /// The value of the last run is loaded from [`Context::storage`] if there is one,
+    /// otherwise it starts at `default`.
+    fn define_setting(&mut self, key: &str, label: &str, kind: SettingKind, default: f32) {
+        let saved = self.storage().get(&format!("setting.{key}"));
+        let setting = Setting {
+            key: key.to_string(),
+            label: label.to_string(),
+            kind,
+            value: kind.clamp(saved.unwrap_or(default)),
+            changed: true,
+        };
+        let list = &mut self.settings.list;
+        match list.iter_mut().find(|s| s.key == key) {
+            Some(existing) => *existing = setting,
+            None => list.push(setting),
+        }
+    }
+
+    pub fn define_bool_setting(&mut self, key: &str, label: &str, default: bool) {
+        self.define_setting(key, label, SettingKind::Bool, f32::from(u8::from(default)));
+    }
+
+    pub fn define_range_setting(
+        &mut self,
+        key: &str,
+        label: &str,
+        min: f32,
+        max: f32,
+        step: f32,
+        default: f32,
+    ) {
+        // values are rounded to whole steps
+        if step <= 0.0 || step.is_nan() {
+            warn!("Setting {} needs a positive step, got {}", key, step);
+            return;
+        }
+        let kind = SettingKind::Range { min, max, step };
+        self.define_setting(key, label, kind, default);
+    }
+
+    /// `default` is the index of the option
+    pub fn define_choice_setting(
+        &mut self,
+        key: &str,
+        label: &str,
+        options: &'static [&'static str],
+        default: usize,
+    ) {
+        let kind = SettingKind::Choice(options);
+        self.define_setting(key, label, kind, default as f32);
+    }
+
+    /// Value of a bool setting, false if it is not defined
+    #[must_use]
+    pub fn setting_bool(&self, key: &str) -> bool {
+        self.setting_value(key) != 0.0
+    }
+
+    /// Value of a range setting, 0 if it is not defined
+    #[must_use]
+    pub fn setting_value(&self, key: &str) -> f32 {
+        self.settings.get(key).map_or(0.0, |setting| setting.value)
+    }
+
+    /// Index of the selected option of a choice setting, 0 if it is not defined
+    #[must_use]
+    pub fn setting_choice(&self, key: &str) -> usize {
+        self.setting_value(key) as usize
+    }
+
+    /// Returns true the first time it is called after the setting was defined or its value
+    /// changed, then false until the next change. Lets games apply a setting only when needed.
+    pub fn take_setting_change(&mut self, key: &str) -> bool {
+        self.settings
+            .list
+            .iter_mut()
+            .find(|s| s.key == key)
+            .is_some_and(|setting| std::mem::take(&mut setting.changed))
+    }
+
+    /// Changes a setting and saves it, bools are 0 or 1 and choices their index
+    pub fn set_setting(&mut self, key: &str, value: f32) {
+        let Some(setting) = self.settings.list.iter_mut().find(|s| s.key == key) else {
+            return;
+        };
+        let value = setting.kind.clamp(value);
+        setting.changed |= setting.value != value;
+        setting.value = value;
+        self.storage().set(&format!("setting.{key}"), &value);
+    }
+}
+
+/// Menu of all settings, opened from the [`PauseScene`] and replacing it while it is open
+#[derive(Default)]
+pub(crate) struct SettingsScene {
+    selected: usize,
+}
+
+impl SettingsScene {
+    fn item_rect<S: GameState>(ctx: &Context<S>, i: usize) -> Rect {
+        let w = ctx.screen_width();
+        let th = TEXT_HEIGHT * ctx.screen_height();
+        Rect::new(w * 0.2, th * 3.0 + th * 1.2 * i as f32, w * 0.6, th * 1.2)
+    }
+}
+
+impl<S: GameState> Scene<S> for SettingsScene {
+    fn update(&mut self, ctx: &mut Context<S>, _delta_time: f32) {
+        if ctx.is_action_pressed(ACTION_PAUSE) {
+            ctx.replace_scene(PauseScene::default());
+            return;
+        }
+        let count = ctx.settings.list.len();
+        if count == 0 {
+            return;
+        }
+        if ctx.is_key_pressed(KeyCode::Up) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if ctx.is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1).min(count - 1);
+        }
+        let mut direction = 0;
+        if ctx.is_key_pressed_loop(KeyCode::Left) {
+            direction = -1;
+        }
+        if ctx.is_key_pressed_loop(KeyCode::Right) || ctx.is_key_pressed(KeyCode::Enter) {
+            direction = 1;
+        }
+        let mouse = ctx.mouse_position();
+        if ctx.is_mouse_button_pressed(MouseButton::Left)
+            && let Some(i) = (0..count).find(|i| Self::item_rect(ctx, *i).contains(mouse))
+        {
+            self.selected = i;
+            // clicks on the left half go down, on the right half up
+            let rect = Self::item_rect(ctx, i);
+            direction = if mouse.x < rect.center().x { -1 } else { 1 };
+        }
+        if direction != 0 {
+            let setting = &ctx.settings.list[self.selected];
+            let key = setting.key.clone();
+            let value = setting.stepped(direction);
+            ctx.set_setting(&key, value);
+        }
+    }
+
+    fn draw(&self, ctx: &Context<S>) {
+        let w = screen_width();
+        let h = screen_height();
+        let th = TEXT_HEIGHT * h;
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
+        draw_text_centered("Settings", w / 2.0, th * 1.5, th * 1.2, WHITE);
+        for (i, setting) in ctx.settings.list.iter().enumerate() {
+            let rect = Self::item_rect(ctx, i);
+            let color = if i == self.selected {
+                draw_rectangle(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    Color::new(1.0, 1.0, 1.0, 0.15),
+                );
+                YELLOW
+            } else {
+                WHITE
+            };
+            let text = format!("{}: < {} >", setting.label, setting.value_text());
+            let center = rect.center();
+            draw_text_centered(&text, center.x, center.y, th * 0.8, color);
+        }
+        let hint = format!(
+            "Up/Down: select, Left/Right: change, {}: back",
+            ctx.action_keys_text(ACTION_PAUSE)
+        );
+        draw_text_centered(&hint, w / 2.0, h - th, th * 0.6, GRAY);
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bool_setting_is_0_or_1() {
+        assert_eq!(SettingKind::Bool.clamp(0.0), 0.0);
+        assert_eq!(SettingKind::Bool.clamp(0.3), 1.0);
+        assert_eq!(SettingKind::Bool.clamp(-2.0), 1.0);
+    }
+
+    #[test]
+    fn range_setting_snaps_to_steps_within_the_range() {
+        let range = SettingKind::Range {
+            min: 10.0,
+            max: 100.0,
+            step: 5.0,
+        };
+        assert_eq!(range.clamp(42.0), 40.0);
+        assert_eq!(range.clamp(43.0), 45.0);
+        assert_eq!(range.clamp(3.0), 10.0);
+        assert_eq!(range.clamp(250.0), 100.0);
+    }
+
+    #[test]
+    fn choice_setting_is_a_valid_index() {
+        let choice = SettingKind::Choice(&["a", "b", "c"]);
+        assert_eq!(choice.clamp(1.4), 1.0);
+        assert_eq!(choice.clamp(-1.0), 0.0);
+        assert_eq!(choice.clamp(7.0), 2.0);
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+#[cfg(not(target_arch = "wasm32"))]
+use macroquad::logging::warn;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+#[cfg(target_arch = "wasm32")]
+use sapp_jsutils::JsObject;
+
+use crate::{Context, GameState};
+
+/// Key-value storage that is kept between runs.
+///
+/// Native builds keep the values in a file in the platform config directory,
+/// wasm builds use the `localStorage` of the browser.
+pub struct Storage {
+    name: String,
+    /// All values on native and in memory, wasm builds read the browser's storage directly
+    values: BTreeMap<String, String>,
+    /// False for storages that only live in memory
+    persistent: bool,
+}
+
+impl Storage {
+    /// Opens the storage with the given name. Different names don't share any values.
+    #[must_use]
+    pub fn open(name: &str) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let values = read_values(&file_path(name));
+        #[cfg(target_arch = "wasm32")]
+        let values = BTreeMap::new();
+        Self {
+            name: name.to_string(),
+            values,
+            persistent: true,
+        }
+    }
+
+    /// Storage that starts with `values` and is never written to disk,
+    /// e.g. for tests and replays
+    pub(crate) fn in_memory(name: &str, values: BTreeMap<String, String>) -> Self {
+        Self {
+            name: name.to_string(),
+            values,
+            persistent: false,
+        }
+    }
+
+    /// Returns the value for `key` if it exists and can be parsed
+    #[must_use]
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get_raw(key)?.parse().ok()
+    }
+
+    pub fn set<T: Display>(&mut self, key: &str, value: &T) {
+        self.set_raw(key, &value.to_string());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage {
+    /// All keys and values
+    pub(crate) fn values(&self) -> BTreeMap<String, String> {
+        self.values.clone()
+    }
+
+    fn get_raw(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_raw(&mut self, key: &str, value: &str) {
+        self.values.insert(key.to_string(), value.to_string());
+        self.write();
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if self.values.remove(key).is_some() {
+            self.write();
+        }
+    }
+
+    fn write(&self) {
+        if !self.persistent {
+            return;
+        }
+        let path = file_path(&self.name);
+        let mut text = String::new();
+        for (key, value) in &self.values {
+            text.push_str(&escape(key));
+            text.push('\t');
+            text.push_str(&escape(value));
+            text.push('\n');
+        }
+        let result = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+        .and_then(|()| std::fs::write(&path, text));
+        if let Err(e) = result {
+            warn!("Failed to write storage {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage {
+    fn key(&self, key: &str) -> JsObject {
+        JsObject::string(&format!("{}/{}", self.name, key))
+    }
+
+    /// All keys and values
+    pub(crate) fn values(&self) -> BTreeMap<String, String> {
+        if !self.persistent {
+            return self.values.clone();
+        }
+        let mut keys = String::new();
+        unsafe { storage_keys_js(JsObject::string(&self.name)) }.to_string(&mut keys);
+        keys.lines()
+            .filter_map(|key| Some((key.to_string(), self.get_raw(key)?)))
+            .collect()
+    }
+
+    fn get_raw(&self, key: &str) -> Option<String> {
+        if !self.persistent {
+            return self.values.get(key).cloned();
+        }
+        let value = unsafe { storage_get_js(self.key(key)) };
+        if value.is_nil() || value.is_undefined() {
+            return None;
+        }
+        let mut text = String::new();
+        value.to_string(&mut text);
+        Some(text)
+    }
+
+    fn set_raw(&mut self, key: &str, value: &str) {
+        if !self.persistent {
+            self.values.insert(key.to_string(), value.to_string());
+            return;
+        }
+        unsafe { storage_set_js(self.key(key), JsObject::string(value)) };
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if !self.persistent {
+            self.values.remove(key);
+            return;
+        }
+        unsafe { storage_remove_js(self.key(key)) };
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe extern "C" {
+    fn storage_get_js(key: JsObject) -> JsObject;
+    fn storage_set_js(key: JsObject, value: JsObject);
+    fn storage_remove_js(key: JsObject);
+    /// Keys of the storage with the given name, one per line
+    fn storage_keys_js(name: JsObject) -> JsObject;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_path(name: &str) -> PathBuf {
+    let dir = dirs::config_dir().unwrap_or_default();
+    dir.join("macroquad_stuff").join(format!("{name}.txt"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_values(path: &std::path::Path) -> BTreeMap<String, String> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    text.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(key, value)| (unescape(key), unescape(value)))
+        .collect()
+}
+
+/// Escapes tabs and newlines so every entry stays on its own line
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+impl<S: GameState> Context<S> {
+    /// Storage of the game, named by [`GameState::storage_name`]
+    pub fn storage(&mut self) -> &mut Storage {
+        self.storage
+            .get_or_insert_with(|| Storage::open(S::storage_name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_text_has_no_tabs_or_newlines_and_unescapes() {
+        let text = "a\tb\nc\\d \\t";
+        let escaped = escape(text);
+        assert!(!escaped.contains(['\t', '\n']));
+        assert_eq!(unescape(&escaped), text);
+    }
+
+    #[test]
+    fn in_memory_storage_reads_its_values() {
+        let values = BTreeMap::from([("size".to_string(), "40".to_string())]);
+        let mut storage = Storage::in_memory("test", values);
+        assert_eq!(storage.get::<usize>("size"), Some(40));
+        assert_eq!(storage.get::<usize>("missing"), None);
+        storage.set("size", &50);
+        storage.remove("missing");
+        assert_eq!(storage.get::<usize>("size"), Some(50));
+        assert_eq!(storage.values().len(), 1);
+    }
+}
@@ -0,0 +1,256 @@
+use macroquad::prelude::*;
+
+/// Smallest size [`TextLayout::draw_in`] shrinks text to
+const MIN_FIT_SIZE: f32 = 8.0;
+
+/// Horizontal alignment of each line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical alignment of the whole block of lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VAlign {
+    /// The top of the first line is at the anchor
+    #[default]
+    Top,
+    Middle,
+    /// The bottom of the last line is at the anchor
+    Bottom,
+}
+
+/// Loads a TTF font to use with [`TextLayout::font`]
+pub fn load_font(bytes: &[u8]) -> Result<Font, macroquad::Error> {
+    load_ttf_font_from_bytes(bytes)
+}
+
+/// Part of a line drawn in one color
+#[derive(Debug, Clone)]
+struct Piece {
+    text: String,
+    color: Color,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Line {
+    pieces: Vec<Piece>,
+    /// Width including trailing spaces, used while wrapping
+    advance: f32,
+    /// Width without trailing spaces, used for alignment
+    width: f32,
+    /// Height above and below the baseline
+    ascent: f32,
+    descent: f32,
+}
+
+/// Text that is wrapped into lines, aligned and drawn in colored spans.
+///
+/// ```ignore
+/// TextLayout::new("Space: ")
+///     .span("draw", YELLOW)
+///     .span(", R: reset", WHITE)
+///     .size(30.0)
+///     .align(HAlign::Center, VAlign::Bottom)
+///     .draw_in(Rect::new(0.0, 0.0, screen_width(), screen_height()));
+/// ```
+///
+/// Lines break at `\n` and, with a maximum width, between words.
+#[derive(Clone)]
+pub struct TextLayout {
+    /// Text and its color, the default color if `None`
+    spans: Vec<(String, Option<Color>)>,
+    size: f32,
+    color: Color,
+    font: Option<Font>,
+    h_align: HAlign,
+    v_align: VAlign,
+    line_spacing: f32,
+    max_width: Option<f32>,
+}
+
+impl TextLayout {
+    /// White text of size 20, anchored at its top left
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self {
+            spans: vec![(text.to_string(), None)],
+            size: 20.0,
+            color: WHITE,
+            font: None,
+            h_align: HAlign::Left,
+            v_align: VAlign::Top,
+            line_spacing: 1.2,
+            max_width: None,
+        }
+    }
+
+    /// Appends text in its own color
+    #[must_use]
+    pub fn span(mut self, text: &str, color: Color) -> Self {
+        self.spans.push((text.to_string(), Some(color)));
+        self
+    }
+
+    /// Font size in pixels
+    #[must_use]
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Color of the text that has no span color
+    #[must_use]
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Font from [`load_font`], macroquad's default font if not set
+    #[must_use]
+    pub fn font(mut self, font: &Font) -> Self {
+        self.font = Some(font.clone());
+        self
+    }
+
+    #[must_use]
+    pub fn align(mut self, h_align: HAlign, v_align: VAlign) -> Self {
+        self.h_align = h_align;
+        self.v_align = v_align;
+        self
+    }
+
+    /// Distance between baselines as a multiple of the size, 1.2 by default
+    #[must_use]
+    pub fn line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// Wraps lines between words to fit into `width`. Words longer than that stay whole.
+    #[must_use]
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    fn measure(&self, text: &str) -> TextDimensions {
+        measure_text(text, self.font.as_ref(), self.size as u16, 1.0)
+    }
+
+    fn lines(&self) -> Vec<Line> {
+        let mut lines = vec![Line::default()];
+        for (text, color) in &self.spans {
+            let color = color.unwrap_or(self.color);
+            for (i, paragraph) in text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Line::default());
+                }
+                for word in paragraph.split_inclusive(' ') {
+                    let line = lines.last_mut().expect("there is always a line");
+                    // only break after a space, spans can continue a word
+                    if let Some(max_width) = self.max_width
+                        && line.pieces.last().is_some_and(|p| p.text.ends_with(' '))
+                        && line.advance + self.measure(word.trim_end()).width > max_width
+                    {
+                        lines.push(Line::default());
+                    }
+                    let line = lines.last_mut().expect("there is always a line");
+                    match line.pieces.last_mut() {
+                        Some(piece) if piece.color == color => piece.text.push_str(word),
+                        _ => line.pieces.push(Piece {
+                            text: word.to_string(),
+                            color,
+                        }),
+                    }
+                    line.advance += self.measure(word).width;
+                }
+            }
+        }
+        for line in &mut lines {
+            let text: String = line
+                .pieces
+                .iter()
+                .map(|piece| piece.text.as_str())
+                .collect();
+            let trimmed = text.trim_end();
+            if !trimmed.is_empty() {
+                let dimensions = self.measure(trimmed);
+                line.width = dimensions.width;
+                line.ascent = dimensions.offset_y;
+                line.descent = dimensions.height - dimensions.offset_y;
+            }
+        }
+        lines
+    }
+
+    /// Height of the block from the top of the first line to the bottom of the last
+    fn block_height(&self, lines: &[Line]) -> f32 {
+        let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+            return 0.0;
+        };
+        let line_height = self.size * self.line_spacing;
+        first.ascent + line_height * (lines.len() - 1) as f32 + last.descent
+    }
+
+    /// Width of the widest line and height of all lines
+    #[must_use]
+    pub fn dimensions(&self) -> Vec2 {
+        let lines = self.lines();
+        let width = lines.iter().map(|line| line.width).fold(0.0, f32::max);
+        vec2(width, self.block_height(&lines))
+    }
+
+    /// Draws the text with the anchor at `x`, `y`, see [`TextLayout::align`]
+    pub fn draw(&self, x: f32, y: f32) {
+        let lines = self.lines();
+        let height = self.block_height(&lines);
+        let top = match self.v_align {
+            VAlign::Top => y,
+            VAlign::Middle => y - height / 2.0,
+            VAlign::Bottom => y - height,
+        };
+        let first_ascent = lines.first().map_or(0.0, |line| line.ascent);
+        let line_height = self.size * self.line_spacing;
+        for (i, line) in lines.iter().enumerate() {
+            let baseline = top + first_ascent + line_height * i as f32;
+            let mut line_x = match self.h_align {
+                HAlign::Left => x,
+                HAlign::Center => x - line.width / 2.0,
+                HAlign::Right => x - line.width,
+            };
+            for piece in &line.pieces {
+                let params = TextParams {
+                    font: self.font.as_ref(),
+                    font_size: self.size as u16,
+                    color: piece.color,
+                    ..Default::default()
+                };
+                line_x += draw_text_ex(&piece.text, line_x, baseline, params).width;
+            }
+        }
+    }
+
+    /// Wraps the text to the width of `rect` and draws it aligned inside of it.
+    /// Text that is too high for `rect` is made smaller until it fits.
+    pub fn draw_in(&self, rect: Rect) {
+        let mut layout = self.clone().max_width(rect.w);
+        while layout.size > MIN_FIT_SIZE && layout.dimensions().y > rect.h {
+            layout.size = (layout.size * 0.9).max(MIN_FIT_SIZE);
+        }
+        let x = match self.h_align {
+            HAlign::Left => rect.x,
+            HAlign::Center => rect.center().x,
+            HAlign::Right => rect.right(),
+        };
+        let y = match self.v_align {
+            VAlign::Top => rect.y,
+            VAlign::Middle => rect.center().y,
+            VAlign::Bottom => rect.bottom(),
+        };
+        layout.draw(x, y);
+    }
+}
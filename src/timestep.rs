@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, frame::FrameInput};
+
+/// Most fixed updates per frame, so a lag spike slows the game down instead of
+/// piling up more and more updates
+const MAX_TICKS_PER_FRAME: f32 = 8.0;
+
+/// Accumulates frame times for [`GameState::tick_rate`]
+pub(crate) struct FixedStep {
+    accumulator: f32,
+    /// How far the time is between the last and the next update
+    alpha: f32,
+    /// Presses of frames without an update, handed to the next update
+    missed: FrameInput,
+    /// Gamepad buttons pressed in frames without an update, by gamepad id
+    missed_gamepads: HashMap<usize, u32>,
+    /// Touches that started in frames without an update, for virtual buttons
+    missed_touches: Vec<Vec2>,
+}
+
+impl Default for FixedStep {
+    /// Alpha starts at 1, so nothing is drawn at the previous positions before the first update
+    fn default() -> Self {
+        Self {
+            accumulator: 0.0,
+            alpha: 1.0,
+            missed: FrameInput::default(),
+            missed_gamepads: HashMap::new(),
+            missed_touches: Vec::new(),
+        }
+    }
+}
+
+impl FixedStep {
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Calls [`GameState::update`] once with the frame time or,
+    /// if the game has a [`GameState::tick_rate`], as often as fixed ticks fit into the elapsed time.
+    ///
+    /// Presses of keys, mouse buttons, gamepad buttons and virtual buttons of this frame
+    /// are only seen by the first of several updates, and presses of frames without
+    /// any update are kept for the next one.
+    pub(crate) async fn update_state(&mut self) {
+        let Some(rate) = S::tick_rate() else {
+            self.fixed_step.alpha = 1.0;
+            let delta = self.frame_time();
+            self.begin_ui(0);
+            S::update(self, delta).await;
+            return;
+        };
+        let step = 1.0 / rate;
+        let fixed = &mut self.fixed_step;
+        fixed.accumulator = (fixed.accumulator + self.input.delta).min(step * MAX_TICKS_PER_FRAME);
+        if fixed.accumulator < step {
+            fixed.missed.add_pressed(&self.input);
+            for (id, buttons) in self.gamepads.pressed() {
+                *fixed.missed_gamepads.entry(*id).or_default() |= buttons;
+            }
+            fixed.missed_touches.extend(self.touches.started());
+        } else {
+            self.input.add_pressed(&fixed.missed);
+            self.gamepads.add_pressed(&fixed.missed_gamepads);
+            self.touches.add_started(&fixed.missed_touches);
+            fixed.missed = FrameInput::default();
+            fixed.missed_gamepads.clear();
+            fixed.missed_touches.clear();
+        }
+        let mut first = true;
+        while self.fixed_step.accumulator >= step {
+            if !first {
+                self.input.clear_pressed();
+                self.gamepads.clear_pressed();
+                self.touches.clear_started();
+            }
+            first = false;
+            // widgets of the last tick are drawn, the ones of frames without a tick stay
+            self.begin_ui(0);
+            S::update(self, step).await;
+            self.fixed_step.accumulator -= step;
+        }
+        self.fixed_step.alpha = self.fixed_step.accumulator / step;
+    }
+
+    /// Interpolation between the last two fixed updates that [`GameState::draw`] gets
+    pub(crate) fn alpha(&self) -> f32 {
+        self.fixed_step.alpha
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::Harness;
+
+    const JUMP: &str = "jump";
+
+    /// Counts the updates that see the jump pressed, at 10 updates per second
+    #[derive(Default)]
+    struct Jumps {
+        jumps: u32,
+    }
+
+    impl GameState for Jumps {
+        fn bg_color(&self) -> Color {
+            BLACK
+        }
+        async fn update(ctx: &mut Context<Self>, _delta_time: f32) {
+            if ctx.is_action_pressed(JUMP) {
+                ctx.state.jumps += 1;
+            }
+        }
+        fn draw(&self, _alpha: f32) {}
+        fn is_paused(&self) -> bool {
+            false
+        }
+        fn reset(&mut self) {}
+        fn tick_rate() -> Option<f32> {
+            Some(10.0)
+        }
+        fn init(ctx: &mut Context<Self>) {
+            ctx.define_action(JUMP, &[KeyCode::Space]);
+            ctx.add_virtual_button(JUMP, "Jump", Rect::new(0.0, 0.0, 0.5, 0.5));
+        }
+    }
+
+    fn tap_button(harness: &mut Harness<Jumps>, delta: f32) {
+        harness.touch(0, TouchPhase::Started, vec2(100.0, 100.0));
+        harness.step_by(delta);
+        harness.touch(0, TouchPhase::Ended, vec2(100.0, 100.0));
+        harness.step();
+    }
+
+    #[test]
+    fn virtual_button_press_in_a_frame_without_update_is_kept() {
+        let mut harness = Harness::<Jumps>::new();
+        tap_button(&mut harness, 1.0 / 60.0);
+        assert_eq!(harness.state().jumps, 0);
+        harness.run(10);
+        assert_eq!(harness.state().jumps, 1);
+    }
+
+    #[test]
+    fn virtual_button_press_is_seen_by_one_of_several_updates() {
+        let mut harness = Harness::<Jumps>::new();
+        tap_button(&mut harness, 0.35);
+        harness.run(10);
+        assert_eq!(harness.state().jumps, 1);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, TEXT_HEIGHT, draw_text_centered};
+
+/// Seconds a finger has to rest before it counts as a long press
+const LONG_PRESS_TIME: f64 = 0.5;
+/// Pixels a finger may move before a tap turns into a drag
+const DRAG_DISTANCE: f32 = 10.0;
+
+/// Gestures recognized from the touches of one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A short touch without moving
+    Tap(Vec2),
+    /// A finger rested without moving, reported once
+    LongPress(Vec2),
+    /// A single finger moved by `delta` since the last frame
+    Drag {
+        start: Vec2,
+        position: Vec2,
+        delta: Vec2,
+    },
+    /// Two fingers changed their distance by the factor `scale` since the last frame
+    Pinch { center: Vec2, scale: f32 },
+}
+
+/// On-screen button that triggers an action while it is touched
+#[derive(Debug, Clone)]
+struct VirtualButton {
+    action: String,
+    label: String,
+    /// Area in screen fractions from 0 to 1
+    rect: Rect,
+}
+
+impl VirtualButton {
+    fn screen_rect(&self, screen: Vec2) -> Rect {
+        let (w, h) = (screen.x, screen.y);
+        Rect::new(
+            self.rect.x * w,
+            self.rect.y * h,
+            self.rect.w * w,
+            self.rect.h * h,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackedTouch {
+    start: Vec2,
+    start_time: f64,
+    position: Vec2,
+    moved: bool,
+    long_pressed: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct Touches {
+    tracked: HashMap<u64, TrackedTouch>,
+    gestures: Vec<Gesture>,
+    pinch_distance: Option<f32>,
+    buttons: Vec<VirtualButton>,
+    /// Positions of touches that started this frame
+    started: Vec<Vec2>,
+    /// Set after the first touch, virtual buttons are only shown on touch screens
+    used: bool,
+    /// Screen size of this frame
+    screen: Vec2,
+}
+
+impl Touches {
+    /// Removes the virtual buttons, they are added again by `init` after a restart
+    pub(crate) fn clear_buttons(&mut self) {
+        self.buttons.clear();
+    }
+
+    /// Turns the touches of this frame into gestures, called once per frame by the game loop
+    pub(crate) fn update(&mut self, touches: &[Touch], now: f64, screen: Vec2) {
+        self.screen = screen;
+        self.gestures.clear();
+        self.started.clear();
+        for touch in touches {
+            self.used = true;
+            match touch.phase {
+                TouchPhase::Started => {
+                    self.started.push(touch.position);
+                    self.tracked.insert(
+                        touch.id,
+                        TrackedTouch {
+                            start: touch.position,
+                            start_time: now,
+                            position: touch.position,
+                            moved: false,
+                            long_pressed: false,
+                        },
+                    );
+                }
+                TouchPhase::Moved | TouchPhase::Stationary => {
+                    let Some(tracked) = self.tracked.get_mut(&touch.id) else {
+                        continue;
+                    };
+                    let delta = touch.position - tracked.position;
+                    tracked.position = touch.position;
+                    if tracked.start.distance(touch.position) > DRAG_DISTANCE {
+                        tracked.moved = true;
+                    }
+                    if touches.len() == 1 && tracked.moved && delta != Vec2::ZERO {
+                        self.gestures.push(Gesture::Drag {
+                            start: tracked.start,
+                            position: touch.position,
+                            delta,
+                        });
+                    }
+                    if !tracked.moved
+                        && !tracked.long_pressed
+                        && now - tracked.start_time > LONG_PRESS_TIME
+                    {
+                        tracked.long_pressed = true;
+                        self.gestures.push(Gesture::LongPress(touch.position));
+                    }
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    // a touch can start and end within the same frame and is never tracked
+                    let tap = self
+                        .tracked
+                        .remove(&touch.id)
+                        .is_none_or(|tracked| !tracked.moved && !tracked.long_pressed);
+                    if touch.phase == TouchPhase::Ended && tap {
+                        self.gestures.push(Gesture::Tap(touch.position));
+                    }
+                }
+            }
+        }
+
+        let active: Vec<Vec2> = self.tracked.values().map(|t| t.position).collect();
+        if let [a, b] = active[..] {
+            let distance = a.distance(b);
+            if let Some(previous) = self.pinch_distance
+                && previous > 0.0
+                && distance != previous
+            {
+                self.gestures.push(Gesture::Pinch {
+                    center: (a + b) / 2.0,
+                    scale: distance / previous,
+                });
+            }
+            self.pinch_distance = Some(distance);
+        } else {
+            self.pinch_distance = None;
+        }
+    }
+
+    /// Positions of touches that started this frame
+    pub(crate) fn started(&self) -> &[Vec2] {
+        &self.started
+    }
+
+    /// Adds touches that started in another frame to this one
+    pub(crate) fn add_started(&mut self, started: &[Vec2]) {
+        self.started.extend(started);
+    }
+
+    /// Forgets the touches that started this frame
+    pub(crate) fn clear_started(&mut self) {
+        self.started.clear();
+    }
+
+    fn button_down(&self, action: &str) -> bool {
+        self.buttons
+            .iter()
+            .filter(|button| button.action == action)
+            .any(|button| {
+                let rect = button.screen_rect(self.screen);
+                self.tracked.values().any(|t| rect.contains(t.position))
+            })
+    }
+
+    fn button_pressed(&self, action: &str) -> bool {
+        self.buttons
+            .iter()
+            .filter(|button| button.action == action)
+            .any(|button| {
+                let rect = button.screen_rect(self.screen);
+                self.started.iter().any(|pos| rect.contains(*pos))
+            })
+    }
+
+    /// Draws the virtual buttons once the player used the touch screen
+    pub(crate) fn draw(&self) {
+        if !self.used {
+            return;
+        }
+        let th = TEXT_HEIGHT * self.screen.y * 0.6;
+        for button in &self.buttons {
+            let rect = button.screen_rect(self.screen);
+            let color = if self.button_down(&button.action) {
+                Color::new(1.0, 1.0, 1.0, 0.4)
+            } else {
+                Color::new(1.0, 1.0, 1.0, 0.15)
+            };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, WHITE);
+            let center = rect.center();
+            draw_text_centered(&button.label, center.x, center.y, th, WHITE);
+        }
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Gestures of this frame
+    #[must_use]
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.touches.gestures
+    }
+
+    /// True once a touch was seen, e.g. to show touch specific hints
+    #[must_use]
+    pub fn is_touch_used(&self) -> bool {
+        self.touches.used
+    }
+
+    /// Adds an on-screen button that triggers the action while it is touched.
+    /// `rect` is given in fractions of the screen size.
+    /// The buttons are only shown after the first touch.
+    pub fn add_virtual_button(&mut self, action: &str, label: &str, rect: Rect) {
+        // replaces the button of the same action and label
+        self.touches
+            .buttons
+            .retain(|button| button.action != action || button.label != label);
+        self.touches.buttons.push(VirtualButton {
+            action: action.to_string(),
+            label: label.to_string(),
+            rect,
+        });
+    }
+
+    /// Returns true if a touch or click at `position` hits a virtual button,
+    /// so games can ignore it for their own input
+    #[must_use]
+    pub fn is_on_virtual_button(&self, position: Vec2) -> bool {
+        self.touches.used
+            && self
+                .touches
+                .buttons
+                .iter()
+                .any(|button| button.screen_rect(self.touches.screen).contains(position))
+    }
+
+    pub(crate) fn is_virtual_button_down(&self, action: &str) -> bool {
+        self.touches.button_down(action)
+    }
+
+    pub(crate) fn is_virtual_button_pressed(&self, action: &str) -> bool {
+        self.touches.button_pressed(action)
+    }
+}
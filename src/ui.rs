@@ -0,0 +1,433 @@
+use macroquad::prelude::*;
+
+use crate::{Context, GameState, HAlign, TextLayout, VAlign};
+
+const BACKGROUND: Color = Color::new(1.0, 1.0, 1.0, 0.15);
+const HOVERED: Color = Color::new(1.0, 1.0, 1.0, 0.25);
+const ACTIVE: Color = Color::new(1.0, 1.0, 1.0, 0.4);
+const FIELD: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+/// Steps of a slider from its minimum to its maximum with the arrow keys
+const SLIDER_STEPS: f32 = 20.0;
+
+/// Something a widget draws, kept until the frame is drawn
+enum Shape {
+    Rect(Rect, Color),
+    Outline(Rect, Color),
+    /// Text in the middle of `rect`, with some padding if aligned to a side
+    Text {
+        text: String,
+        rect: Rect,
+        align: HAlign,
+        color: Color,
+    },
+    /// Arrow pointing down, for drop-downs
+    Arrow(Rect, Color),
+}
+
+impl Shape {
+    fn draw(&self) {
+        match self {
+            Shape::Rect(rect, color) => draw_rectangle(rect.x, rect.y, rect.w, rect.h, *color),
+            Shape::Outline(rect, color) => {
+                draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, *color);
+            }
+            Shape::Text {
+                text,
+                rect,
+                align,
+                color,
+            } => {
+                let padding = rect.h * 0.25;
+                let x = match align {
+                    HAlign::Left => rect.x + padding,
+                    HAlign::Center => rect.center().x,
+                    HAlign::Right => rect.right() - padding,
+                };
+                TextLayout::new(text)
+                    .size(rect.h * 0.6)
+                    .color(*color)
+                    .align(*align, VAlign::Middle)
+                    .draw(x, rect.center().y);
+            }
+            Shape::Arrow(rect, color) => {
+                let center = rect.center();
+                let size = rect.h * 0.2;
+                draw_triangle(
+                    vec2(center.x - size, center.y - size / 2.0),
+                    vec2(center.x + size, center.y - size / 2.0),
+                    vec2(center.x, center.y + size / 2.0),
+                    *color,
+                );
+            }
+        }
+    }
+}
+
+/// Widgets of the game or of one scene
+#[derive(Default)]
+struct Layer {
+    shapes: Vec<Shape>,
+    /// Drawn over all other widgets of the layer, e.g. an open drop-down list
+    popups: Vec<Shape>,
+    /// Widgets created in this update and in the one before
+    count: usize,
+    last_count: usize,
+    /// Area of the open drop-down list, widgets below it don't get the pointer
+    popup_area: Option<Rect>,
+    blocked_area: Option<Rect>,
+}
+
+/// Widget identified by its layer and the order it was created in
+type WidgetId = (usize, usize);
+
+/// State of the immediate-mode widgets, which are created anew in every update.
+///
+/// Layer 0 belongs to the game and layer `i + 1` to the i-th scene on the stack,
+/// so the widgets of a scene are drawn over the scene and below the scenes above it.
+#[derive(Default)]
+pub(crate) struct Ui {
+    layers: Vec<Layer>,
+    /// Layer of the game or scene that is updated right now
+    layer: usize,
+    /// Widget that gets the keyboard, moved with Tab and lost by clicking elsewhere
+    focus: Option<WidgetId>,
+    open_dropdown: Option<WidgetId>,
+    /// Slider that is dragged
+    dragged: Option<WidgetId>,
+    /// Text field that had the keyboard last
+    typing: Option<WidgetId>,
+}
+
+impl Ui {
+    /// Forgets the layers of scenes that were removed
+    pub(crate) fn truncate(&mut self, layers: usize) {
+        self.layers.truncate(layers);
+        let widgets = [
+            &mut self.focus,
+            &mut self.open_dropdown,
+            &mut self.dragged,
+            &mut self.typing,
+        ];
+        for widget in widgets {
+            if widget.is_some_and(|(layer, _)| layer >= layers) {
+                *widget = None;
+            }
+        }
+    }
+
+    /// Draws the widgets of the layer, called by the game loop after the game or scene
+    pub(crate) fn draw(&self, layer: usize) {
+        if let Some(layer) = self.layers.get(layer) {
+            for shape in layer.shapes.iter().chain(&layer.popups) {
+                shape.draw();
+            }
+        }
+    }
+
+    fn layer(&mut self) -> &mut Layer {
+        let layer = self.layer;
+        &mut self.layers[layer]
+    }
+}
+
+/// What happened to a widget in this update
+struct Interaction {
+    id: WidgetId,
+    hovered: bool,
+    /// Pressed by the mouse or a touch
+    clicked: bool,
+    focused: bool,
+}
+
+/// Mouse or first touch
+struct Pointer {
+    position: Vec2,
+    down: bool,
+    pressed: bool,
+}
+
+impl<S: GameState> Context<S> {
+    /// Starts the widgets of a layer, called before the game or a scene is updated
+    pub(crate) fn begin_ui(&mut self, layer: usize) {
+        let ui = &mut self.ui;
+        if ui.layers.len() <= layer {
+            ui.layers.resize_with(layer + 1, Layer::default);
+        }
+        ui.layer = layer;
+        let current = ui.layer();
+        current.last_count = current.count;
+        current.count = 0;
+        current.shapes.clear();
+        current.popups.clear();
+        current.blocked_area = current.popup_area.take();
+        let count = current.last_count;
+        if self.is_key_pressed(KeyCode::Tab) && count > 0 {
+            // after the last widget the keyboard goes back to the game
+            self.ui.focus = match self.ui.focus {
+                Some((focus_layer, i)) if focus_layer == layer => {
+                    (i + 1 < count).then_some((layer, i + 1))
+                }
+                _ => Some((layer, 0)),
+            };
+        }
+    }
+
+    fn pointer(&self) -> Pointer {
+        if let Some(touch) = self.input.touches.first() {
+            let ended = matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled);
+            Pointer {
+                position: touch.position,
+                down: !ended,
+                pressed: touch.phase == TouchPhase::Started,
+            }
+        } else {
+            Pointer {
+                position: self.mouse_position(),
+                down: self.is_mouse_button_down(MouseButton::Left),
+                pressed: self.is_mouse_button_pressed(MouseButton::Left),
+            }
+        }
+    }
+
+    fn interact(&mut self, rect: Rect) -> Interaction {
+        let pointer = self.pointer();
+        let id = (self.ui.layer, self.ui.layer().count);
+        let layer = self.ui.layer();
+        layer.count += 1;
+        let blocked = layer
+            .blocked_area
+            .is_some_and(|area| area.contains(pointer.position))
+            && self.ui.open_dropdown != Some(id);
+        let hovered = !blocked && rect.contains(pointer.position);
+        let mut focused = self.ui.focus == Some(id);
+        // a click elsewhere takes the keyboard away
+        if focused && pointer.pressed && !hovered {
+            self.ui.focus = None;
+            focused = false;
+        }
+        Interaction {
+            id,
+            hovered,
+            clicked: hovered && pointer.pressed,
+            focused,
+        }
+    }
+
+    fn push_shape(&mut self, shape: Shape) {
+        self.ui.layer().shapes.push(shape);
+    }
+
+    /// Background and outline of a widget
+    fn push_frame(&mut self, rect: Rect, widget: &Interaction) {
+        let background = if widget.hovered && self.pointer().down {
+            ACTIVE
+        } else if widget.hovered {
+            HOVERED
+        } else {
+            BACKGROUND
+        };
+        self.push_shape(Shape::Rect(rect, background));
+        let outline = if widget.focused { YELLOW } else { WHITE };
+        self.push_shape(Shape::Outline(rect, outline));
+    }
+
+    fn push_text(&mut self, text: &str, rect: Rect, align: HAlign, widget: &Interaction) {
+        let color = if widget.focused { YELLOW } else { WHITE };
+        self.push_shape(Shape::Text {
+            text: text.to_string(),
+            rect,
+            align,
+            color,
+        });
+    }
+
+    /// Gives the keyboard to the i-th widget created in the current update,
+    /// e.g. to move through a menu with the arrow keys
+    pub fn set_ui_focus(&mut self, index: Option<usize>) {
+        self.ui.focus = index.map(|i| (self.ui.layer, i));
+    }
+
+    /// Index of the widget of the current layer that has the keyboard, see [`Context::set_ui_focus`]
+    #[must_use]
+    pub fn ui_focus(&self) -> Option<usize> {
+        self.ui
+            .focus
+            .filter(|(layer, _)| *layer == self.ui.layer)
+            .map(|(_, i)| i)
+    }
+
+    /// True while a text field has the keyboard, so games can ignore typed keys
+    #[must_use]
+    pub fn is_typing(&self) -> bool {
+        self.ui.focus.is_some() && self.ui.focus == self.ui.typing
+    }
+
+    /// Button that returns true when it is clicked, touched or activated with Enter
+    pub fn ui_button(&mut self, rect: Rect, label: &str) -> bool {
+        let widget = self.interact(rect);
+        self.push_frame(rect, &widget);
+        self.push_text(label, rect, HAlign::Center, &widget);
+        widget.clicked || (widget.focused && self.is_key_pressed(KeyCode::Enter))
+    }
+
+    /// Box with a label that toggles `checked`, returns true if it changed
+    pub fn ui_checkbox(&mut self, rect: Rect, label: &str, checked: &mut bool) -> bool {
+        let widget = self.interact(rect);
+        let changed = widget.clicked || (widget.focused && self.is_key_pressed(KeyCode::Enter));
+        if changed {
+            *checked = !*checked;
+        }
+        let size = rect.h * 0.6;
+        let check = Rect::new(rect.x + rect.h * 0.2, rect.y + rect.h * 0.2, size, size);
+        self.push_frame(check, &widget);
+        if *checked {
+            let mark = Rect::new(
+                check.x + size * 0.2,
+                check.y + size * 0.2,
+                size * 0.6,
+                size * 0.6,
+            );
+            self.push_shape(Shape::Rect(mark, WHITE));
+        }
+        let text = Rect::new(rect.x + rect.h, rect.y, rect.w - rect.h, rect.h);
+        self.push_text(label, text, HAlign::Left, &widget);
+        changed
+    }
+
+    /// Bar to drag `value` between `min` and `max`, or change it with Left/Right
+    /// when focused. The label is shown on the bar. Returns true if the value changed.
+    pub fn ui_slider(
+        &mut self,
+        rect: Rect,
+        label: &str,
+        value: &mut f32,
+        min: f32,
+        max: f32,
+    ) -> bool {
+        let widget = self.interact(rect);
+        let pointer = self.pointer();
+        if widget.clicked {
+            self.ui.dragged = Some(widget.id);
+        }
+        if !pointer.down && self.ui.dragged == Some(widget.id) {
+            self.ui.dragged = None;
+        }
+        let old = *value;
+        if self.ui.dragged == Some(widget.id) {
+            let progress = ((pointer.position.x - rect.x) / rect.w).clamp(0.0, 1.0);
+            *value = min + progress * (max - min);
+        }
+        if widget.focused {
+            let step = (max - min) / SLIDER_STEPS;
+            if self.is_key_pressed_loop(KeyCode::Left) {
+                *value = (*value - step).max(min);
+            }
+            if self.is_key_pressed_loop(KeyCode::Right) {
+                *value = (*value + step).min(max);
+            }
+        }
+        let progress = ((*value - min) / (max - min)).clamp(0.0, 1.0);
+        self.push_frame(rect, &widget);
+        let filled = Rect::new(rect.x, rect.y, rect.w * progress, rect.h);
+        self.push_shape(Shape::Rect(filled, BACKGROUND));
+        let handle = Rect::new(filled.right() - 2.0, rect.y, 4.0, rect.h);
+        self.push_shape(Shape::Rect(handle, WHITE));
+        self.push_text(label, rect, HAlign::Center, &widget);
+        *value != old
+    }
+
+    /// Shows the selected option and opens a list of all options when clicked.
+    /// Left/Right change the option when focused. Returns true if the selection changed.
+    pub fn ui_dropdown(&mut self, rect: Rect, options: &[&str], selected: &mut usize) -> bool {
+        let widget = self.interact(rect);
+        let old = *selected;
+        let open = self.ui.open_dropdown == Some(widget.id);
+        if widget.clicked || (widget.focused && self.is_key_pressed(KeyCode::Enter)) {
+            self.ui.open_dropdown = if open { None } else { Some(widget.id) };
+        }
+        if widget.focused && !options.is_empty() {
+            if self.is_key_pressed_loop(KeyCode::Left) {
+                *selected = (*selected + options.len() - 1) % options.len();
+            }
+            if self.is_key_pressed_loop(KeyCode::Right) {
+                *selected = (*selected + 1) % options.len();
+            }
+        }
+
+        if open {
+            let pointer = self.pointer();
+            let list = Rect::new(rect.x, rect.bottom(), rect.w, rect.h * options.len() as f32);
+            let mut popups = Vec::new();
+            popups.push(Shape::Rect(list, FIELD));
+            for (i, option) in options.iter().enumerate() {
+                let item = Rect::new(rect.x, rect.bottom() + rect.h * i as f32, rect.w, rect.h);
+                let hovered = item.contains(pointer.position);
+                if hovered {
+                    popups.push(Shape::Rect(item, HOVERED));
+                    if pointer.pressed {
+                        *selected = i;
+                        self.ui.open_dropdown = None;
+                    }
+                }
+                let color = if i == *selected { YELLOW } else { WHITE };
+                popups.push(Shape::Text {
+                    text: (*option).to_string(),
+                    rect: item,
+                    align: HAlign::Left,
+                    color,
+                });
+            }
+            popups.push(Shape::Outline(list, WHITE));
+            if pointer.pressed && !widget.hovered && !list.contains(pointer.position) {
+                self.ui.open_dropdown = None;
+            }
+            let layer = self.ui.layer();
+            layer.popups.extend(popups);
+            layer.popup_area = Some(list);
+        }
+
+        self.push_frame(rect, &widget);
+        let text = options.get(*selected).copied().unwrap_or_default();
+        let label = Rect::new(rect.x, rect.y, rect.w - rect.h, rect.h);
+        self.push_text(text, label, HAlign::Left, &widget);
+        let arrow = Rect::new(rect.right() - rect.h, rect.y, rect.h, rect.h);
+        self.push_shape(Shape::Arrow(arrow, WHITE));
+        *selected != old
+    }
+
+    /// Single line of editable text. Clicking it gives it the keyboard,
+    /// Enter or a click elsewhere gives it back. Returns true if the text changed.
+    pub fn ui_text_field(&mut self, rect: Rect, text: &mut String) -> bool {
+        let widget = self.interact(rect);
+        if widget.clicked {
+            self.ui.focus = Some(widget.id);
+        }
+        let focused = self.ui.focus == Some(widget.id);
+        let old = text.clone();
+        if focused {
+            self.ui.typing = Some(widget.id);
+            text.extend(self.chars_typed().iter().filter(|c| !c.is_control()));
+            if self.is_key_pressed_loop(KeyCode::Backspace) {
+                text.pop();
+            }
+            if self.is_key_pressed(KeyCode::Enter) {
+                self.ui.focus = None;
+            }
+        }
+        self.push_shape(Shape::Rect(rect, FIELD));
+        let outline = if focused { YELLOW } else { WHITE };
+        self.push_shape(Shape::Outline(rect, outline));
+        let cursor = if focused && (self.time() * 2.0).fract() < 0.5 {
+            "_"
+        } else {
+            ""
+        };
+        self.push_shape(Shape::Text {
+            text: format!("{text}{cursor}"),
+            rect,
+            align: HAlign::Left,
+            color: WHITE,
+        });
+        *text != old
+    }
+}
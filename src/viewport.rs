@@ -0,0 +1,215 @@
+use macroquad::prelude::*;
+
+use crate::{Context, GameState};
+
+/// How the virtual resolution of a [`Viewport`] is scaled to the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// Keeps the aspect ratio and shows everything, with bars on two sides
+    #[default]
+    Fit,
+    /// Keeps the aspect ratio and covers the screen, cutting off two sides
+    Fill,
+    /// Covers the screen exactly, distorting the aspect ratio
+    Stretch,
+    /// Like [`ScaleMode::Fit`] but only by whole multiples, for crisp pixel art.
+    /// Screens smaller than the virtual resolution scale down as with `Fit`.
+    Integer,
+}
+
+/// A virtual resolution the game draws in, scaled to the screen.
+///
+/// Returned from [`GameState::viewport`], the game then draws in world units from
+/// `(0, 0)` to `(width, height)` at any screen size. Positions from the input are
+/// still in screen pixels, [`Context::screen_to_world`] converts them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    size: Vec2,
+    mode: ScaleMode,
+    /// Color of the bars around the virtual area
+    letterbox: Color,
+}
+
+impl Viewport {
+    #[must_use]
+    pub fn new(width: f32, height: f32, mode: ScaleMode) -> Self {
+        Self {
+            size: vec2(width, height),
+            mode,
+            letterbox: BLACK,
+        }
+    }
+
+    /// Color of the bars that hide what is drawn outside of the virtual area, black by default
+    #[must_use]
+    pub fn letterbox(mut self, color: Color) -> Self {
+        self.letterbox = color;
+        self
+    }
+
+    /// The virtual resolution
+    #[must_use]
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> ScaleMode {
+        self.mode
+    }
+
+    /// Screen pixels per world unit on both axes
+    #[must_use]
+    pub fn scale(&self, screen: Vec2) -> Vec2 {
+        let fit = screen / self.size;
+        match self.mode {
+            ScaleMode::Fit => Vec2::splat(fit.min_element()),
+            ScaleMode::Fill => Vec2::splat(fit.max_element()),
+            ScaleMode::Stretch => fit,
+            ScaleMode::Integer if fit.min_element() >= 1.0 => {
+                Vec2::splat(fit.min_element().floor())
+            }
+            ScaleMode::Integer => Vec2::splat(fit.min_element()),
+        }
+    }
+
+    /// Where the virtual area ends up on the screen, centered
+    #[must_use]
+    pub fn screen_rect(&self, screen: Vec2) -> Rect {
+        let size = self.size * self.scale(screen);
+        let offset = (screen - size) / 2.0;
+        Rect::new(offset.x, offset.y, size.x, size.y)
+    }
+
+    #[must_use]
+    pub fn screen_to_world(&self, screen: Vec2, point: Vec2) -> Vec2 {
+        (point - self.screen_rect(screen).point()) / self.scale(screen)
+    }
+
+    #[must_use]
+    pub fn world_to_screen(&self, screen: Vec2, point: Vec2) -> Vec2 {
+        point * self.scale(screen) + self.screen_rect(screen).point()
+    }
+
+    /// Camera that shows the virtual area at [`Viewport::screen_rect`]
+    #[must_use]
+    pub fn camera(&self, screen: Vec2) -> Camera2D {
+        // the whole screen in world units, so nothing is stretched into the bars
+        let top_left = self.screen_to_world(screen, Vec2::ZERO);
+        let size = self.screen_to_world(screen, screen) - top_left;
+        Camera2D {
+            target: top_left + size / 2.0,
+            zoom: 2.0 / size,
+            ..Default::default()
+        }
+    }
+
+    /// Covers the screen around the virtual area with the letterbox color
+    fn draw_letterbox(&self, screen: Vec2) {
+        let rect = self.screen_rect(screen);
+        let color = self.letterbox;
+        if rect.x > 0.0 {
+            draw_rectangle(0.0, 0.0, rect.x, screen.y, color);
+            draw_rectangle(rect.right(), 0.0, screen.x - rect.right(), screen.y, color);
+        }
+        if rect.y > 0.0 {
+            draw_rectangle(0.0, 0.0, screen.x, rect.y, color);
+            draw_rectangle(
+                0.0,
+                rect.bottom(),
+                screen.x,
+                screen.y - rect.bottom(),
+                color,
+            );
+        }
+    }
+}
+
+impl<S: GameState> Context<S> {
+    /// Converts a position on the screen, e.g. of the mouse or a touch, to world units
+    /// of the [`GameState::viewport`]. Returns the position unchanged without a viewport.
+    #[must_use]
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        S::viewport().map_or(point, |viewport| {
+            viewport.screen_to_world(self.input.screen, point)
+        })
+    }
+
+    /// Converts a position in world units to the screen, e.g. to place widgets next to the game
+    #[must_use]
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        S::viewport().map_or(point, |viewport| {
+            viewport.world_to_screen(self.input.screen, point)
+        })
+    }
+
+    /// The mouse position in world units, see [`Context::screen_to_world`]
+    #[must_use]
+    pub fn world_mouse_position(&self) -> Vec2 {
+        self.screen_to_world(self.mouse_position())
+    }
+
+    /// Draws the game through the camera of its viewport, if it has one
+    pub(crate) fn draw_state(&self) {
+        let Some(viewport) = S::viewport() else {
+            self.state.draw(self.alpha());
+            return;
+        };
+        // the actual window, the recorded screen size may differ in replays
+        let screen = vec2(screen_width(), screen_height());
+        set_camera(&viewport.camera(screen));
+        self.state.draw(self.alpha());
+        set_default_camera();
+        viewport.draw_letterbox(screen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_keeps_the_aspect_ratio_with_bars_on_the_sides() {
+        let viewport = Viewport::new(800.0, 600.0, ScaleMode::Fit);
+        let screen = vec2(1600.0, 900.0);
+        assert_eq!(viewport.scale(screen), Vec2::splat(1.5));
+        assert_eq!(
+            viewport.screen_rect(screen),
+            Rect::new(200.0, 0.0, 1200.0, 900.0)
+        );
+    }
+
+    #[test]
+    fn scale_modes() {
+        let screen = vec2(1000.0, 450.0);
+        let scale = |mode| Viewport::new(400.0, 300.0, mode).scale(screen);
+        assert_eq!(scale(ScaleMode::Fit), Vec2::splat(1.5));
+        assert_eq!(scale(ScaleMode::Fill), Vec2::splat(2.5));
+        assert_eq!(scale(ScaleMode::Stretch), vec2(2.5, 1.5));
+        assert_eq!(scale(ScaleMode::Integer), Vec2::splat(1.0));
+        // smaller than the virtual resolution, scaled down like fit
+        let small = Viewport::new(400.0, 300.0, ScaleMode::Integer);
+        assert_eq!(small.scale(vec2(200.0, 300.0)), Vec2::splat(0.5));
+    }
+
+    #[test]
+    fn screen_to_world_reverses_world_to_screen() {
+        let viewport = Viewport::new(800.0, 600.0, ScaleMode::Fit);
+        let screen = vec2(1600.0, 900.0);
+        assert_eq!(
+            viewport.screen_to_world(screen, vec2(200.0, 0.0)),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            viewport.screen_to_world(screen, vec2(800.0, 450.0)),
+            vec2(400.0, 300.0)
+        );
+        let point = vec2(123.0, 456.0);
+        let there = viewport.world_to_screen(screen, point);
+        assert!(
+            viewport
+                .screen_to_world(screen, there)
+                .abs_diff_eq(point, 1e-3)
+        );
+    }
+}